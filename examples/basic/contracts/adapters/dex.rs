@@ -1,6 +1,187 @@
-use crate::math::ratios::basis_points::apply_bps_fee;
+use super::{Quote, Router, FEE_STEPS};
+use crate::math::amount::Amount;
+use crate::math::ratios::basis_points::{
+    apply_bps_fee, checked_apply_bps_fee, Bps, BPS_DENOMINATOR,
+};
+use crate::math::ratios::{checked_div_floor, checked_div_floor_wide};
 
-pub fn build_dex_route(amount: u64) -> u64 {
-    let adjusted = apply_bps_fee(amount, 80);
-    adjusted / 2
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "DexRouteConfigFields"))]
+pub struct DexRouteConfig {
+    pub fee_bps: u64,
+    pub split_divisor: u64,
+}
+
+impl Default for DexRouteConfig {
+    fn default() -> Self {
+        Self {
+            fee_bps: 80,
+            split_divisor: 2,
+        }
+    }
+}
+
+impl DexRouteConfig {
+    /// Returns `None` when `fee_bps > 10_000` or `split_divisor == 0`.
+    pub fn new(fee_bps: u64, split_divisor: u64) -> Option<Self> {
+        Bps::new(fee_bps)?;
+        if split_divisor == 0 {
+            return None;
+        }
+        Some(Self {
+            fee_bps,
+            split_divisor,
+        })
+    }
+}
+
+/// Manifest form of `DexRouteConfig`; deserializing goes through `DexRouteConfig::new`.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DexRouteConfigFields {
+    fee_bps: u64,
+    split_divisor: u64,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<DexRouteConfigFields> for DexRouteConfig {
+    type Error = crate::math::ratios::MathError;
+
+    fn try_from(fields: DexRouteConfigFields) -> Result<Self, Self::Error> {
+        Self::new(fields.fee_bps, fields.split_divisor)
+            .ok_or(crate::math::ratios::MathError::OutOfRange)
+    }
+}
+
+pub fn build_dex_route(amount: Amount) -> Amount {
+    build_dex_route_with(amount, &DexRouteConfig::default()).expect("default dex config is valid")
+}
+
+/// Returns `None` for a config `DexRouteConfig::new` would reject
+/// (`fee_bps > 10_000` or `split_divisor == 0`).
+pub fn build_dex_route_with(amount: Amount, cfg: &DexRouteConfig) -> Option<Amount> {
+    let adjusted = checked_apply_bps_fee(amount.get(), cfg.fee_bps)?;
+    checked_div_floor(adjusted as u128, cfg.split_divisor as u128).map(Amount::new)
+}
+
+/// `build_dex_route` with each intermediate value recorded.
+pub fn quote_dex(amount: Amount) -> Quote {
+    let cfg = DexRouteConfig::default();
+    let adjusted = apply_bps_fee(amount, cfg.fee_bps);
+    let output = checked_div_floor(adjusted.get() as u128, cfg.split_divisor as u128)
+        .map(Amount::new)
+        .expect("default dex config is valid");
+    Quote {
+        output,
+        fee_bps_total: cfg.fee_bps,
+        steps: vec![
+            ("input", amount),
+            ("after 80bps fee", adjusted),
+            ("after /2", output),
+        ],
+    }
+}
+
+/// `build_dex_route` computed with widened, saturating arithmetic throughout:
+/// the result is clamped to `0..=u64::MAX` and never panics.
+pub fn build_dex_route_saturating(amount: Amount) -> Amount {
+    let cfg = DexRouteConfig::default();
+    let amount = amount.get();
+    let bps = cfg.fee_bps.min(BPS_DENOMINATOR) as u128;
+    let fee = checked_div_floor_wide(
+        (amount as u128).saturating_mul(bps),
+        BPS_DENOMINATOR as u128,
+    )
+    .expect("BPS_DENOMINATOR is nonzero");
+    let adjusted = (amount as u128).saturating_sub(fee);
+    Amount::new(checked_div_floor(adjusted, cfg.split_divisor as u128).unwrap_or(0))
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DexRouter {
+    config: DexRouteConfig,
+}
+
+impl DexRouter {
+    /// Returns `None` when `DexRouteConfig::new` would reject `config`.
+    pub fn new(config: DexRouteConfig) -> Option<Self> {
+        let config = DexRouteConfig::new(config.fee_bps, config.split_divisor)?;
+        Some(Self { config })
+    }
+
+    pub fn config(&self) -> &DexRouteConfig {
+        &self.config
+    }
+}
+
+impl Router for DexRouter {
+    fn name(&self) -> &'static str {
+        "dex"
+    }
+
+    fn quote(&self, amount: Amount) -> Amount {
+        build_dex_route_with(amount, &self.config).expect("config validated by DexRouter::new")
+    }
+
+    /// One fee plus the split division.
+    fn estimated_steps(&self, _amount: Amount) -> u64 {
+        FEE_STEPS + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quote_dex_matches_plain_route() {
+        for amount in [0, 1, 999, 1_000_000, u64::MAX].map(Amount::new) {
+            let quote = quote_dex(amount);
+            assert_eq!(quote.output, build_dex_route(amount));
+            assert_eq!(quote.steps.last(), Some(&("after /2", quote.output)));
+        }
+    }
+
+    #[test]
+    fn zero_split_divisor_is_rejected() {
+        assert_eq!(DexRouteConfig::new(80, 0), None);
+        let cfg = DexRouteConfig {
+            fee_bps: 80,
+            split_divisor: 0,
+        };
+        assert_eq!(build_dex_route_with(Amount::new(10_000), &cfg), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn config_round_trips_through_json() {
+        let cfg = DexRouteConfig::new(30, 3).unwrap();
+        let json = serde_json::to_string(&cfg).unwrap();
+        assert_eq!(serde_json::from_str::<DexRouteConfig>(&json).unwrap(), cfg);
+    }
+
+    #[test]
+    fn saturating_route_handles_u64_max() {
+        for amount in [0, 1, 10_000, u64::MAX].map(Amount::new) {
+            assert_eq!(build_dex_route_saturating(amount), build_dex_route(amount));
+        }
+        assert_eq!(
+            build_dex_route_saturating(Amount::MAX),
+            Amount::new(9_149_585_060_559_937_601)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn config_rejects_out_of_range_values() {
+        for manifest in [
+            r#"{"fee_bps":20000,"split_divisor":2}"#,
+            r#"{"fee_bps":80,"split_divisor":0}"#,
+            r#"{"fee_bps":80,"split_divisor":2,"extra":1}"#,
+        ] {
+            assert!(serde_json::from_str::<DexRouteConfig>(manifest).is_err());
+        }
+    }
 }
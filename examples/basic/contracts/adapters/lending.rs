@@ -1,8 +1,324 @@
-use crate::math::ratios::basis_points::apply_bps_fee;
-use crate::math::ratios::interpolation::weighted_average;
+use super::{Quote, Router, BLEND_STEPS, FEE_STEPS};
+use crate::math::amount::Amount;
+use crate::math::ratios::basis_points::{apply_bps_fee, try_apply_bps_fee, Bps, BPS_DENOMINATOR};
+use crate::math::ratios::interpolation::{
+    checked_weighted_average, try_weighted_average, weighted_average, PERCENT_DENOMINATOR,
+};
+use crate::math::ratios::{checked_div_floor_wide, resolve_overflow, MathError};
 
-pub fn build_lending_route(amount: u64) -> u64 {
-    let conservative = apply_bps_fee(amount, 20);
-    let aggressive = apply_bps_fee(amount, 5);
-    weighted_average(conservative, aggressive, 60)
+pub const SECONDS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LendingError {
+    InsufficientLiquidity {
+        available: Amount,
+        min_liquidity: Amount,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "LendingRouteConfigFields"))]
+pub struct LendingRouteConfig {
+    pub conservative_bps: u64,
+    pub aggressive_bps: u64,
+    pub conservative_weight: u64,
+}
+
+impl Default for LendingRouteConfig {
+    fn default() -> Self {
+        Self {
+            conservative_bps: 20,
+            aggressive_bps: 5,
+            conservative_weight: 60,
+        }
+    }
+}
+
+impl LendingRouteConfig {
+    /// Returns `None` when either bps exceeds 10_000 or
+    /// `conservative_weight > 100`.
+    pub fn new(
+        conservative_bps: u64,
+        aggressive_bps: u64,
+        conservative_weight: u64,
+    ) -> Option<Self> {
+        Bps::new(conservative_bps)?;
+        Bps::new(aggressive_bps)?;
+        if conservative_weight > PERCENT_DENOMINATOR {
+            return None;
+        }
+        Some(Self {
+            conservative_bps,
+            aggressive_bps,
+            conservative_weight,
+        })
+    }
+}
+
+/// Manifest form of `LendingRouteConfig`; deserializing goes through `LendingRouteConfig::new`.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LendingRouteConfigFields {
+    conservative_bps: u64,
+    aggressive_bps: u64,
+    conservative_weight: u64,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<LendingRouteConfigFields> for LendingRouteConfig {
+    type Error = MathError;
+
+    fn try_from(fields: LendingRouteConfigFields) -> Result<Self, Self::Error> {
+        Self::new(
+            fields.conservative_bps,
+            fields.aggressive_bps,
+            fields.conservative_weight,
+        )
+        .ok_or(MathError::OutOfRange)
+    }
+}
+
+pub fn build_lending_route(amount: Amount) -> Amount {
+    build_lending_route_with(amount, &LendingRouteConfig::default())
+        .expect("default lending config is valid")
+}
+
+/// Returns `InsufficientLiquidity` when `available_liquidity < min_liquidity`,
+/// otherwise the same quote as `build_lending_route`.
+pub fn build_lending_route_guarded(
+    amount: Amount,
+    available_liquidity: Amount,
+    min_liquidity: Amount,
+) -> Result<Amount, LendingError> {
+    if available_liquidity < min_liquidity {
+        return Err(LendingError::InsufficientLiquidity {
+            available: available_liquidity,
+            min_liquidity,
+        });
+    }
+    Ok(build_lending_route(amount))
+}
+
+/// Returns `MathError::BpsOutOfRange` when either bps exceeds 10_000 and
+/// `MathError::WeightOutOfRange` when `cfg.conservative_weight > 100`.
+pub fn build_lending_route_with(
+    amount: Amount,
+    cfg: &LendingRouteConfig,
+) -> Result<Amount, MathError> {
+    let conservative = try_apply_bps_fee(amount.get(), cfg.conservative_bps)?;
+    let aggressive = try_apply_bps_fee(amount.get(), cfg.aggressive_bps)?;
+    checked_weighted_average(conservative, aggressive, cfg.conservative_weight).map(Amount::new)
+}
+
+/// `build_lending_route` with each intermediate value recorded.
+pub fn quote_lending(amount: Amount) -> Quote {
+    let cfg = LendingRouteConfig::default();
+    let conservative = apply_bps_fee(amount, cfg.conservative_bps);
+    let aggressive = apply_bps_fee(amount, cfg.aggressive_bps);
+    let output = try_weighted_average(conservative, aggressive, cfg.conservative_weight)
+        .expect("default lending config is valid");
+    Quote {
+        output,
+        fee_bps_total: weighted_average(
+            cfg.conservative_bps,
+            cfg.aggressive_bps,
+            cfg.conservative_weight,
+        ),
+        steps: vec![
+            ("input", amount),
+            ("after 20bps conservative fee", conservative),
+            ("after 5bps aggressive fee", aggressive),
+            ("after 60/40 blend", output),
+        ],
+    }
+}
+
+/// `build_lending_route` run through the `u128` instances of `apply_bps_fee`
+/// and `weighted_average`, narrowing to `u64` with saturation at `u64::MAX`.
+pub fn build_lending_route_saturating(amount: Amount) -> Amount {
+    let cfg = LendingRouteConfig::default();
+    let amount = amount.get() as u128;
+    let conservative = apply_bps_fee(amount, cfg.conservative_bps);
+    let aggressive = apply_bps_fee(amount, cfg.aggressive_bps);
+    let blended = weighted_average(conservative, aggressive, cfg.conservative_weight);
+    Amount::new(u64::try_from(blended).unwrap_or(u64::MAX))
+}
+
+/// `principal` plus simple (non-compounding) interest at `apr_bps` over
+/// `seconds`, using a `SECONDS_PER_YEAR` year.
+pub fn accrue_interest(principal: u64, apr_bps: u64, seconds: u64) -> u64 {
+    let accrued = (principal as u128)
+        .checked_mul(apr_bps as u128)
+        .and_then(|scaled| scaled.checked_mul(seconds as u128))
+        .and_then(|scaled| {
+            checked_div_floor_wide(scaled, BPS_DENOMINATOR as u128 * SECONDS_PER_YEAR as u128)
+        })
+        .and_then(|interest| u64::try_from(principal as u128 + interest).ok());
+    resolve_overflow(accrued, u64::MAX)
+}
+
+pub fn build_lending_route_with_accrual(
+    amount: Amount,
+    apr_bps: u64,
+    seconds: u64,
+    cfg: &LendingRouteConfig,
+) -> Result<Amount, MathError> {
+    build_lending_route_with(accrue_interest(amount.get(), apr_bps, seconds).into(), cfg)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LendingRouter {
+    config: LendingRouteConfig,
+}
+
+impl LendingRouter {
+    /// Returns `None` when `LendingRouteConfig::new` would reject `config`.
+    pub fn new(config: LendingRouteConfig) -> Option<Self> {
+        let config = LendingRouteConfig::new(
+            config.conservative_bps,
+            config.aggressive_bps,
+            config.conservative_weight,
+        )?;
+        Some(Self { config })
+    }
+
+    pub fn config(&self) -> &LendingRouteConfig {
+        &self.config
+    }
+}
+
+impl Router for LendingRouter {
+    fn name(&self) -> &'static str {
+        "lending"
+    }
+
+    fn quote(&self, amount: Amount) -> Amount {
+        build_lending_route_with(amount, &self.config)
+            .expect("config validated by LendingRouter::new")
+    }
+
+    /// Two fees plus the blend.
+    fn estimated_steps(&self, _amount: Amount) -> u64 {
+        2 * FEE_STEPS + BLEND_STEPS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quote_lending_matches_plain_route() {
+        for amount in [0, 1, 999, 1_000_000, u64::MAX].map(Amount::new) {
+            let quote = quote_lending(amount);
+            assert_eq!(quote.output, build_lending_route(amount));
+            assert_eq!(
+                quote.steps.last(),
+                Some(&("after 60/40 blend", quote.output))
+            );
+        }
+    }
+
+    #[test]
+    fn quote_lending_reports_effective_fee() {
+        assert_eq!(quote_lending(Amount::new(1_000_000)).fee_bps_total, 14);
+    }
+
+    #[cfg(not(feature = "checked-math"))]
+    #[test]
+    fn accrue_interest_saturates_past_u64_max() {
+        assert_eq!(
+            accrue_interest(u64::MAX, 10_000, SECONDS_PER_YEAR),
+            u64::MAX
+        );
+    }
+
+    #[cfg(feature = "checked-math")]
+    #[test]
+    #[should_panic(expected = "arithmetic overflow")]
+    fn accrue_interest_panics_past_u64_max_under_checked_math() {
+        accrue_interest(u64::MAX, 10_000, SECONDS_PER_YEAR);
+    }
+
+    #[test]
+    fn accrue_interest_adds_a_year_of_interest() {
+        assert_eq!(accrue_interest(1_000_000, 500, SECONDS_PER_YEAR), 1_050_000);
+    }
+
+    #[test]
+    fn zero_duration_accrues_nothing() {
+        assert_eq!(accrue_interest(1_000_000, 500, 0), 1_000_000);
+        assert_eq!(accrue_interest(1_000_000, 0, SECONDS_PER_YEAR), 1_000_000);
+    }
+
+    #[test]
+    fn one_year_accrual_matches_simple_interest() {
+        // 123_456_789 * 725 / 10_000 = 8_950_617, rounded down.
+        let accrued = 123_456_789 + 8_950_617;
+        assert_eq!(accrue_interest(123_456_789, 725, SECONDS_PER_YEAR), accrued);
+        let cfg = LendingRouteConfig::default();
+        assert_eq!(
+            build_lending_route_with_accrual(Amount::new(123_456_789), 725, SECONDS_PER_YEAR, &cfg),
+            build_lending_route_with(Amount::new(accrued), &cfg)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn config_round_trips_through_json() {
+        let cfg = LendingRouteConfig::new(40, 10, 75).unwrap();
+        let json = serde_json::to_string(&cfg).unwrap();
+        assert_eq!(
+            serde_json::from_str::<LendingRouteConfig>(&json).unwrap(),
+            cfg
+        );
+    }
+
+    #[test]
+    fn guarded_route_checks_the_liquidity_threshold() {
+        let amount = Amount::new(10_000);
+        assert_eq!(
+            build_lending_route_guarded(amount, Amount::new(1_000), Amount::new(1_000)),
+            Ok(build_lending_route(amount))
+        );
+        assert_eq!(
+            build_lending_route_guarded(amount, Amount::new(1_001), Amount::new(1_000)),
+            Ok(build_lending_route(amount))
+        );
+        assert_eq!(
+            build_lending_route_guarded(amount, Amount::new(999), Amount::new(1_000)),
+            Err(LendingError::InsufficientLiquidity {
+                available: Amount::new(999),
+                min_liquidity: Amount::new(1_000),
+            })
+        );
+    }
+
+    #[test]
+    fn saturating_route_handles_u64_max() {
+        for amount in [0, 1, 10_000, u64::MAX].map(Amount::new) {
+            assert_eq!(
+                build_lending_route_saturating(amount),
+                build_lending_route(amount)
+            );
+        }
+        assert_eq!(
+            build_lending_route_saturating(Amount::MAX),
+            Amount::new(18_420_918_632_006_358_243)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn config_rejects_out_of_range_values() {
+        for manifest in [
+            r#"{"conservative_bps":20000,"aggressive_bps":5,"conservative_weight":60}"#,
+            r#"{"conservative_bps":20,"aggressive_bps":20000,"conservative_weight":60}"#,
+            r#"{"conservative_bps":20,"aggressive_bps":5,"conservative_weight":101}"#,
+        ] {
+            assert!(serde_json::from_str::<LendingRouteConfig>(manifest).is_err());
+        }
+    }
 }
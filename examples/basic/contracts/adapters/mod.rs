@@ -1,9 +1,358 @@
 pub mod dex;
 pub mod lending;
+pub mod staking;
 
-pub use dex::build_dex_route;
-pub use lending::build_lending_route;
+pub use dex::{build_dex_route, DexRouter};
+pub use lending::{build_lending_route, LendingRouter};
+pub use staking::{build_staking_route, StakingRouter};
 
-pub fn summarize_routes(amount: u64) -> u64 {
-    build_dex_route(amount) + build_lending_route(amount)
+use crate::math::amount::Amount;
+use crate::math::ratios::interpolation::PERCENT_DENOMINATOR;
+use crate::math::ratios::{checked_div_floor, resolve_overflow};
+
+/// Arithmetic operations in one `apply_bps_fee` call (two divs, rem, two muls,
+/// add, sub).
+pub const FEE_STEPS: u64 = 7;
+/// Arithmetic operations in one two-way `weighted_average` call.
+pub const BLEND_STEPS: u64 = 12;
+
+pub trait Router {
+    fn name(&self) -> &'static str;
+    fn quote(&self, amount: Amount) -> Amount;
+    /// Deterministic, heuristic count of arithmetic operations for a quote,
+    /// built from `FEE_STEPS` and `BLEND_STEPS`.
+    fn estimated_steps(&self, amount: Amount) -> u64;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouterKind {
+    Dex,
+    Lending,
+    Staking,
+}
+
+/// Quotes `amount` through the `build_*_route` matching `kind`.
+pub fn route(kind: RouterKind, amount: Amount) -> Amount {
+    match kind {
+        RouterKind::Dex => build_dex_route(amount),
+        RouterKind::Lending => build_lending_route(amount),
+        RouterKind::Staking => build_staking_route(amount),
+    }
+}
+
+/// A route output together with how it was derived. `fee_bps_total` is the
+/// effective fee actually charged on the input (rounded down); `steps` records
+/// the value after each transformation, in order, and its last entry equals
+/// `output`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Quote {
+    pub output: Amount,
+    pub fee_bps_total: u64,
+    pub steps: Vec<(&'static str, Amount)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteBreakdown {
+    pub dex: Amount,
+    pub lending: Amount,
+    pub total: Amount,
+}
+
+pub fn summarize_routes(amount: Amount) -> Amount {
+    summarize_routes_detailed(amount).total
+}
+
+/// Returns `None` when the route quotes overflow.
+pub fn try_summarize_routes(amount: Amount) -> Option<Amount> {
+    let dex = DexRouter::default();
+    let lending = LendingRouter::default();
+    try_summarize_routes_over(&[&dex, &lending], amount)
+}
+
+pub fn summarize_routes_detailed(amount: Amount) -> RouteBreakdown {
+    let dex = DexRouter::default();
+    let lending = LendingRouter::default();
+    let routers: [&dyn Router; 2] = [&dex, &lending];
+    let [dex, lending] = routers.map(|router| router.quote(amount));
+    RouteBreakdown {
+        dex,
+        lending,
+        total: sum_quotes([dex, lending]),
+    }
+}
+
+pub fn summarize_routes_with_staking(amount: Amount) -> Amount {
+    let dex = DexRouter::default();
+    let lending = LendingRouter::default();
+    summarize_routes_over(&[&dex, &lending, &StakingRouter], amount)
+}
+
+pub fn summarize_routes_over(routers: &[&dyn Router], amount: Amount) -> Amount {
+    sum_quotes(routers.iter().map(|router| router.quote(amount)))
+}
+
+/// Returns `None` when the quotes overflow.
+pub fn try_summarize_routes_over(routers: &[&dyn Router], amount: Amount) -> Option<Amount> {
+    routers.iter().try_fold(Amount::ZERO, |total, router| {
+        total.checked_add(router.quote(amount))
+    })
+}
+
+fn sum_quotes(quotes: impl IntoIterator<Item = Amount>) -> Amount {
+    quotes.into_iter().fold(Amount::ZERO, |total, quote| {
+        resolve_overflow(total.checked_add(quote), Amount::MAX)
+    })
+}
+
+/// Ties resolve toward dex.
+pub fn best_route(amount: Amount) -> (&'static str, Amount) {
+    let dex = DexRouter::default();
+    let lending = LendingRouter::default();
+    best_route_over(&[&dex, &lending], amount).expect("router list is non-empty")
+}
+
+/// Ties resolve toward the earliest router in `routers`; `None` when empty.
+pub fn best_route_over(routers: &[&dyn Router], amount: Amount) -> Option<(&'static str, Amount)> {
+    let mut best: Option<(&'static str, Amount)> = None;
+    for router in routers {
+        let quote = router.quote(amount);
+        if best.is_none_or(|(_, best_quote)| quote > best_quote) {
+            best = Some((router.name(), quote));
+        }
+    }
+    best
+}
+
+/// Sends each route `amount * weight / 100` and sums the quotes; the last
+/// route also receives the integer-division remainder. Returns `None` when
+/// `weights` is empty, does not sum to 100, or the quotes overflow.
+pub fn split_and_route(amount: Amount, weights: &[(RouterKind, u64)]) -> Option<Amount> {
+    let (&(last_kind, _), rest) = weights.split_last()?;
+    let total_weight = weights
+        .iter()
+        .try_fold(0u64, |sum, &(_, weight)| sum.checked_add(weight))?;
+    if total_weight != PERCENT_DENOMINATOR {
+        return None;
+    }
+
+    let mut allocated = Amount::ZERO;
+    let mut output = Amount::ZERO;
+    for &(kind, weight) in rest {
+        let share = checked_div_floor(
+            amount.get() as u128 * weight as u128,
+            PERCENT_DENOMINATOR as u128,
+        )?;
+        let share = Amount::new(share);
+        allocated = allocated.saturating_add(share);
+        output = output.checked_add(route(kind, share))?;
+    }
+    output.checked_add(route(last_kind, amount.saturating_sub(allocated)))
+}
+
+/// Like `best_route_over` but ranks routers by `quote - estimated_steps *
+/// cost_per_step` (saturating at zero); returns the winner's raw quote.
+pub fn best_route_net_of_cost(
+    routers: &[&dyn Router],
+    amount: Amount,
+    cost_per_step: u64,
+) -> Option<(&'static str, Amount)> {
+    let mut best: Option<(&'static str, Amount, Amount)> = None;
+    for router in routers {
+        let quote = router.quote(amount);
+        let cost = router.estimated_steps(amount).saturating_mul(cost_per_step);
+        let net = quote.saturating_sub(Amount::new(cost));
+        if best.is_none_or(|(_, _, best_net)| net > best_net) {
+            best = Some((router.name(), quote, net));
+        }
+    }
+    best.map(|(name, quote, _)| (name, quote))
+}
+
+/// Feeds each hop's output into the next; an empty `hops` returns `amount`.
+pub fn route_multihop(amount: Amount, hops: &[RouterKind]) -> Amount {
+    hops.iter()
+        .fold(amount, |carried, &kind| route(kind, carried))
+}
+
+pub fn route_multihop_over(routers: &[&dyn Router], amount: Amount) -> Amount {
+    routers
+        .iter()
+        .fold(amount, |carried, router| router.quote(carried))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn breakdown_total_is_sum_of_routes() {
+        for amount in [0, 1, 10_000, 123_456_789].map(Amount::new) {
+            let breakdown = summarize_routes_detailed(amount);
+            assert_eq!(
+                breakdown.dex.checked_add(breakdown.lending),
+                Some(breakdown.total)
+            );
+            assert_eq!(breakdown.dex, build_dex_route(amount));
+            assert_eq!(breakdown.lending, build_lending_route(amount));
+            assert_eq!(summarize_routes(amount), breakdown.total);
+        }
+    }
+
+    #[cfg(not(feature = "checked-math"))]
+    #[test]
+    fn overflowing_total_saturates() {
+        assert_eq!(summarize_routes(Amount::MAX), Amount::MAX);
+        assert_eq!(try_summarize_routes(Amount::MAX), None);
+    }
+
+    #[cfg(feature = "checked-math")]
+    #[test]
+    #[should_panic(expected = "arithmetic overflow")]
+    fn overflowing_total_panics_under_checked_math() {
+        summarize_routes(Amount::MAX);
+    }
+
+    #[test]
+    fn best_route_picks_lending_by_default() {
+        let amount = Amount::new(10_000);
+        assert_eq!(best_route(amount), ("lending", build_lending_route(amount)));
+    }
+
+    #[test]
+    fn best_route_picks_dex_when_it_quotes_more() {
+        let dex = DexRouter::new(dex::DexRouteConfig::new(0, 1).unwrap()).unwrap();
+        let lending = LendingRouter::default();
+        let amount = Amount::new(10_000);
+        assert_eq!(
+            best_route_over(&[&lending, &dex], amount),
+            Some(("dex", amount))
+        );
+    }
+
+    #[test]
+    fn best_route_ties_resolve_toward_dex() {
+        assert_eq!(best_route(Amount::ZERO), ("dex", Amount::ZERO));
+        assert_eq!(best_route_over(&[], Amount::new(1)), None);
+    }
+
+    #[test]
+    fn staking_total_adds_the_staking_quote() {
+        let amount = Amount::new(10_000);
+        assert_eq!(
+            summarize_routes(amount).checked_add(build_staking_route(amount)),
+            Some(summarize_routes_with_staking(amount))
+        );
+    }
+
+    #[test]
+    fn dex_is_estimated_cheaper_than_lending() {
+        let amount = Amount::new(10_000);
+        let dex = DexRouter::default();
+        let lending = LendingRouter::default();
+        assert!(dex.estimated_steps(amount) < lending.estimated_steps(amount));
+        assert_eq!(
+            lending.estimated_steps(amount),
+            StakingRouter.estimated_steps(amount)
+        );
+    }
+
+    #[test]
+    fn step_cost_can_flip_the_best_route() {
+        let amount = Amount::new(10_000);
+        let dex = DexRouter::default();
+        let lending = LendingRouter::default();
+        let routers: [&dyn Router; 2] = [&dex, &lending];
+        assert_eq!(
+            best_route_net_of_cost(&routers, amount, 0),
+            Some(("lending", build_lending_route(amount)))
+        );
+        assert_eq!(
+            best_route_net_of_cost(&routers, amount, 300),
+            Some(("dex", build_dex_route(amount)))
+        );
+        assert_eq!(best_route_net_of_cost(&[], amount, 1), None);
+    }
+
+    #[test]
+    fn multihop_threads_each_output_into_the_next_hop() {
+        let amount = Amount::new(10_000);
+        assert_eq!(route_multihop(amount, &[]), amount);
+        assert_eq!(
+            route_multihop(amount, &[RouterKind::Dex]),
+            build_dex_route(amount)
+        );
+        assert_eq!(
+            route_multihop(amount, &[RouterKind::Dex, RouterKind::Lending]),
+            build_lending_route(build_dex_route(amount))
+        );
+        assert_eq!(
+            route_multihop(amount, &[RouterKind::Lending, RouterKind::Dex]),
+            build_dex_route(build_lending_route(amount))
+        );
+    }
+
+    #[test]
+    fn multihop_over_routers_matches_kinds() {
+        let amount = Amount::new(10_000);
+        let dex = DexRouter::default();
+        let lending = LendingRouter::default();
+        assert_eq!(route_multihop_over(&[], amount), amount);
+        assert_eq!(
+            route_multihop_over(&[&dex, &lending], amount),
+            route_multihop(amount, &[RouterKind::Dex, RouterKind::Lending])
+        );
+    }
+
+    #[test]
+    fn try_summarize_reports_overflow() {
+        let amount = Amount::new(10_000);
+        assert_eq!(try_summarize_routes(amount), Some(summarize_routes(amount)));
+        let dex = DexRouter::new(dex::DexRouteConfig::new(0, 1).unwrap()).unwrap();
+        assert_eq!(try_summarize_routes_over(&[&dex, &dex], Amount::MAX), None);
+        assert_eq!(
+            try_summarize_routes_over(&[&dex, &dex], Amount::new(u64::MAX / 2)),
+            Some(Amount::new(u64::MAX - 1))
+        );
+    }
+
+    #[test]
+    fn route_dispatches_to_the_matching_builder() {
+        for amount in [0, 1, 999, 10_000, 123_456_789].map(Amount::new) {
+            assert_eq!(route(RouterKind::Dex, amount), build_dex_route(amount));
+            assert_eq!(
+                route(RouterKind::Lending, amount),
+                build_lending_route(amount)
+            );
+            assert_eq!(
+                route(RouterKind::Staking, amount),
+                build_staking_route(amount)
+            );
+        }
+    }
+
+    #[test]
+    fn split_sends_the_remainder_to_the_last_route() {
+        let weights = [(RouterKind::Dex, 50), (RouterKind::Lending, 50)];
+        let expected = build_dex_route(Amount::new(5_000))
+            .checked_add(build_lending_route(Amount::new(5_001)));
+        assert_eq!(split_and_route(Amount::new(10_001), &weights), expected);
+        assert_eq!(expected, Some(Amount::new(7_474)));
+        assert_eq!(
+            split_and_route(Amount::new(10_001), &[(RouterKind::Staking, 100)]),
+            Some(build_staking_route(Amount::new(10_001)))
+        );
+    }
+
+    #[test]
+    fn split_rejects_bad_weights() {
+        let amount = Amount::new(10_000);
+        for weights in [
+            &[(RouterKind::Dex, 50), (RouterKind::Lending, 49)][..],
+            &[(RouterKind::Dex, 50), (RouterKind::Lending, 51)],
+            &[],
+            &[(RouterKind::Dex, u64::MAX), (RouterKind::Lending, 101)],
+        ] {
+            assert_eq!(split_and_route(amount, weights), None);
+        }
+    }
 }
@@ -0,0 +1,40 @@
+use super::{Router, BLEND_STEPS, FEE_STEPS};
+use crate::math::amount::Amount;
+use crate::math::ratios::basis_points::apply_bps_fee;
+use crate::math::ratios::interpolation::weighted_average;
+
+pub fn build_staking_route(amount: Amount) -> Amount {
+    let entered = apply_bps_fee(amount, 10);
+    let locked = apply_bps_fee(entered, 150);
+    weighted_average(entered, locked, 30)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct StakingRouter;
+
+impl Router for StakingRouter {
+    fn name(&self) -> &'static str {
+        "staking"
+    }
+
+    fn quote(&self, amount: Amount) -> Amount {
+        build_staking_route(amount)
+    }
+
+    /// Entry fee, lock discount and the blend.
+    fn estimated_steps(&self, _amount: Amount) -> u64 {
+        2 * FEE_STEPS + BLEND_STEPS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn staking_route_quotes_zero_and_typical_amounts() {
+        assert_eq!(build_staking_route(Amount::ZERO), Amount::ZERO);
+        assert_eq!(build_staking_route(Amount::new(10_000)), Amount::new(9_885));
+        assert_eq!(StakingRouter.quote(Amount::new(10_000)), Amount::new(9_885));
+    }
+}
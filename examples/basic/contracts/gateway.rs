@@ -1,16 +1,615 @@
 mod adapters;
 mod risk;
 
-use adapters::{dex::build_dex_route, lending::build_lending_route};
+use crate::math::amount::Amount;
+use crate::math::ratios::basis_points::{apply_bps_fee, Bps};
+use crate::math::ratios::resolve_overflow;
+use adapters::{dex::build_dex_route, lending::build_lending_route, route, RouterKind};
 use risk::throttler::ThrottleGuard;
-use crate::math::ratios::basis_points::apply_bps_fee;
+use risk::{OwnerGuard, ReentrancyGuard, RiskError};
 
-pub fn rebalance(amount: u64, throttle_bps: u64) -> u64 {
-    let guard = ThrottleGuard::new(throttle_bps);
-    let guarded = guard.clamp(amount);
+pub const DEFAULT_PROTOCOL_FEE_BPS: u64 = 25;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "GatewayConfigFields"))]
+pub struct GatewayConfig {
+    pub protocol_fee_bps: u64,
+    pub throttle_bps: u64,
+}
+
+impl GatewayConfig {
+    /// Returns `None` when `protocol_fee_bps > 10_000` or
+    /// `throttle_bps > 10_000`.
+    pub fn new(protocol_fee_bps: u64, throttle_bps: u64) -> Option<Self> {
+        Bps::new(protocol_fee_bps)?;
+        Bps::new(throttle_bps)?;
+        Some(Self {
+            protocol_fee_bps,
+            throttle_bps,
+        })
+    }
+}
+
+/// Manifest form of `GatewayConfig`; deserializing goes through `GatewayConfig::new`.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GatewayConfigFields {
+    protocol_fee_bps: u64,
+    throttle_bps: u64,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<GatewayConfigFields> for GatewayConfig {
+    type Error = crate::math::ratios::MathError;
+
+    fn try_from(fields: GatewayConfigFields) -> Result<Self, Self::Error> {
+        Self::new(fields.protocol_fee_bps, fields.throttle_bps)
+            .ok_or(crate::math::ratios::MathError::BpsOutOfRange)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GatewayError {
+    BelowMinimum { got: Amount, min_out: Amount },
+    ThrottleRejected,
+    InvalidConfig,
+    Expired { deadline: u64, now: u64 },
+    InvalidAmount(TradeError),
+    Risk(RiskError),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradeError {
+    ZeroAmount,
+}
+
+impl From<TradeError> for GatewayError {
+    fn from(err: TradeError) -> Self {
+        GatewayError::InvalidAmount(err)
+    }
+}
+
+impl From<RiskError> for GatewayError {
+    fn from(err: RiskError) -> Self {
+        GatewayError::Risk(err)
+    }
+}
+
+/// Rejects amounts an entry point cannot meaningfully act on. The math helpers
+/// stay tolerant of zero; only entry points call this.
+pub const fn validate_amount(amount: Amount) -> Result<Amount, TradeError> {
+    if amount.get() == 0 {
+        return Err(TradeError::ZeroAmount);
+    }
+    Ok(amount)
+}
+
+/// Stage hooks for `rebalance_observed`; every method defaults to a no-op.
+pub trait RebalanceObserver {
+    fn on_guarded(&mut self, _amount: Amount) {}
+    fn on_routes(&mut self, _dex: Amount, _lending: Amount) {}
+    fn on_net(&mut self, _net: Amount) {}
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopObserver;
+
+impl RebalanceObserver for NoopObserver {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RebalanceReport {
+    pub guarded: Amount,
+    pub dex_quote: Amount,
+    pub lending_quote: Amount,
+    pub gross: Amount,
+    pub net: Amount,
+}
+
+/// Stateful entry point holding a config, the owner allowed to change it, and
+/// the `ReentrancyGuard` every `Gateway::rebalance` takes before doing any work.
+#[derive(Debug)]
+pub struct Gateway {
+    config: GatewayConfig,
+    owner: OwnerGuard,
+    lock: ReentrancyGuard,
+}
+
+impl Gateway {
+    /// Returns `None` when `GatewayConfig::new` would reject `config`.
+    pub fn new(owner: [u8; 32], config: GatewayConfig) -> Option<Self> {
+        let config = GatewayConfig::new(config.protocol_fee_bps, config.throttle_bps)?;
+        Some(Self {
+            config,
+            owner: OwnerGuard::new(owner),
+            lock: ReentrancyGuard::new(),
+        })
+    }
+
+    pub fn config(&self) -> &GatewayConfig {
+        &self.config
+    }
+
+    /// Returns `Risk(RiskError::Unauthorized)` unless `caller` is the owner and
+    /// `InvalidConfig` when `GatewayConfig::new` would reject `config`; either
+    /// way the config is left unchanged.
+    pub fn set_config(
+        &mut self,
+        caller: &[u8; 32],
+        config: GatewayConfig,
+    ) -> Result<(), GatewayError> {
+        self.owner.require_owner(caller)?;
+        self.config = GatewayConfig::new(config.protocol_fee_bps, config.throttle_bps)
+            .ok_or(GatewayError::InvalidConfig)?;
+        Ok(())
+    }
+
+    /// `rebalance_detailed_observed` under the gateway's config. Returns
+    /// `Risk(RiskError::Reentered)` when reached again (e.g. from `obs`) while
+    /// an earlier call on the same gateway is still running, and
+    /// `InvalidAmount` for a zero `amount`.
+    pub fn rebalance(
+        &self,
+        amount: Amount,
+        obs: &mut dyn RebalanceObserver,
+    ) -> Result<RebalanceReport, GatewayError> {
+        let _entered = self.lock.enter()?;
+        validate_amount(amount)?;
+        Ok(rebalance_detailed_observed(amount, &self.config, obs)
+            .expect("config validated by Gateway::new and set_config"))
+    }
+}
+
+/// Clamps `amount` with a `ThrottleGuard` and quotes it through `kind`, without
+/// the protocol fee.
+pub fn guarded_route(amount: Amount, throttle_bps: u64, kind: RouterKind) -> Amount {
+    route(
+        kind,
+        ThrottleGuard::new(throttle_bps).clamp(amount.get()).into(),
+    )
+}
+
+pub fn rebalance(amount: Amount, throttle_bps: u64) -> Amount {
+    rebalance_detailed(amount, throttle_bps).net
+}
+
+/// `(throttle_bps, net)` for each candidate throttle, as `rebalance` would
+/// produce it.
+pub fn rebalance_preview(amount: Amount, throttle_bps_candidates: &[u64]) -> Vec<(u64, Amount)> {
+    throttle_bps_candidates
+        .iter()
+        .map(|&throttle_bps| (throttle_bps, rebalance(amount, throttle_bps)))
+        .collect()
+}
+
+/// Returns `InvalidAmount` for a zero `amount`, `ThrottleRejected` when
+/// `throttle_bps` is not a valid throttle and `BelowMinimum` when the net
+/// output falls short of `min_out`.
+pub fn rebalance_checked(
+    amount: Amount,
+    throttle_bps: u64,
+    min_out: Amount,
+) -> Result<Amount, GatewayError> {
+    validate_amount(amount)?;
+    ThrottleGuard::new(throttle_bps)
+        .try_clamp(amount.get())
+        .ok_or(GatewayError::ThrottleRejected)?;
+    let net = rebalance(amount, throttle_bps);
+    if net < min_out {
+        return Err(GatewayError::BelowMinimum { got: net, min_out });
+    }
+    Ok(net)
+}
+
+/// Rejects with `Expired` when `now > deadline`, then behaves exactly like
+/// `rebalance_checked`.
+pub fn rebalance_checked_with_deadline(
+    amount: Amount,
+    throttle_bps: u64,
+    min_out: Amount,
+    deadline: u64,
+    now: u64,
+) -> Result<Amount, GatewayError> {
+    if now > deadline {
+        return Err(GatewayError::Expired { deadline, now });
+    }
+    rebalance_checked(amount, throttle_bps, min_out)
+}
+
+pub fn rebalance_detailed(amount: Amount, throttle_bps: u64) -> RebalanceReport {
+    rebalance_report(amount, &default_config(throttle_bps), &mut NoopObserver)
+}
+
+pub fn rebalance_observed(
+    amount: Amount,
+    throttle_bps: u64,
+    obs: &mut dyn RebalanceObserver,
+) -> Amount {
+    rebalance_report(amount, &default_config(throttle_bps), obs).net
+}
+
+fn default_config(throttle_bps: u64) -> GatewayConfig {
+    GatewayConfig {
+        protocol_fee_bps: DEFAULT_PROTOCOL_FEE_BPS,
+        throttle_bps,
+    }
+}
+
+/// Returns `None` when `GatewayConfig::new` would reject `cfg`.
+pub fn rebalance_with(amount: Amount, cfg: &GatewayConfig) -> Option<Amount> {
+    rebalance_detailed_with(amount, cfg).map(|report| report.net)
+}
+
+/// Returns `None` when `GatewayConfig::new` would reject `cfg`.
+pub fn rebalance_detailed_with(amount: Amount, cfg: &GatewayConfig) -> Option<RebalanceReport> {
+    rebalance_detailed_observed(amount, cfg, &mut NoopObserver)
+}
+
+/// Returns `None`, before notifying `obs`, when `GatewayConfig::new` would
+/// reject `cfg`.
+pub fn rebalance_detailed_observed(
+    amount: Amount,
+    cfg: &GatewayConfig,
+    obs: &mut dyn RebalanceObserver,
+) -> Option<RebalanceReport> {
+    let cfg = GatewayConfig::new(cfg.protocol_fee_bps, cfg.throttle_bps)?;
+    Some(rebalance_report(amount, &cfg, obs))
+}
+
+/// Shared by the validated entry points and the `throttle_bps` wrappers, which
+/// keep passing an out-of-range throttle straight through.
+fn rebalance_report(
+    amount: Amount,
+    cfg: &GatewayConfig,
+    obs: &mut dyn RebalanceObserver,
+) -> RebalanceReport {
+    let guard = ThrottleGuard::new(cfg.throttle_bps);
+    let guarded = Amount::new(guard.clamp(amount.get()));
+    obs.on_guarded(guarded);
 
     let dex_quote = build_dex_route(guarded);
     let lending_quote = build_lending_route(guarded);
+    obs.on_routes(dex_quote, lending_quote);
+    let gross = resolve_overflow(dex_quote.checked_add(lending_quote), Amount::MAX);
+
+    let net = apply_bps_fee(gross, cfg.protocol_fee_bps);
+    obs.on_net(net);
+
+    RebalanceReport {
+        guarded,
+        dex_quote,
+        lending_quote,
+        gross,
+        net,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OWNER: [u8; 32] = [7; 32];
+
+    struct ReentrantObserver<'a> {
+        gateway: &'a Gateway,
+        nested: Option<Result<RebalanceReport, GatewayError>>,
+    }
+
+    impl RebalanceObserver for ReentrantObserver<'_> {
+        fn on_guarded(&mut self, amount: Amount) {
+            self.nested = Some(self.gateway.rebalance(amount, &mut NoopObserver));
+        }
+    }
+
+    #[test]
+    fn nested_rebalance_is_rejected() {
+        let gateway = Gateway::new(
+            OWNER,
+            GatewayConfig::new(DEFAULT_PROTOCOL_FEE_BPS, 5_000).unwrap(),
+        )
+        .unwrap();
+        let mut obs = ReentrantObserver {
+            gateway: &gateway,
+            nested: None,
+        };
+        let outer = gateway.rebalance(Amount::new(10_000), &mut obs).unwrap();
+        assert_eq!(outer.net, Amount::new(7_455));
+        assert_eq!(
+            obs.nested,
+            Some(Err(GatewayError::Risk(RiskError::Reentered)))
+        );
+        assert_eq!(
+            gateway.rebalance(Amount::new(10_000), &mut NoopObserver),
+            Ok(outer)
+        );
+    }
+
+    #[test]
+    fn only_owner_can_set_config() {
+        let initial = GatewayConfig::new(DEFAULT_PROTOCOL_FEE_BPS, 5_000).unwrap();
+        let updated = GatewayConfig::new(DEFAULT_PROTOCOL_FEE_BPS, 2_500).unwrap();
+        let mut gateway = Gateway::new(OWNER, initial.clone()).unwrap();
+
+        assert_eq!(
+            gateway.set_config(&[8; 32], updated.clone()),
+            Err(GatewayError::Risk(RiskError::Unauthorized))
+        );
+        assert_eq!(gateway.config(), &initial);
+
+        assert_eq!(gateway.set_config(&OWNER, updated.clone()), Ok(()));
+        assert_eq!(gateway.config(), &updated);
+    }
+
+    #[test]
+    fn u64_callers_migrate_through_into() {
+        let net: u64 = rebalance(10_000.into(), 5_000).into();
+        assert_eq!(net, 7_455);
+        assert_eq!(
+            rebalance_checked(10_000.into(), 5_000, 7_456.into()),
+            Err(GatewayError::BelowMinimum {
+                got: Amount::new(7_455),
+                min_out: Amount::new(7_456),
+            })
+        );
+    }
+
+    #[cfg(not(feature = "checked-math"))]
+    #[test]
+    fn overflowing_gross_saturates() {
+        let report = rebalance_detailed(Amount::MAX, 10_000);
+        assert_eq!(report.gross, Amount::MAX);
+        assert_eq!(
+            report.net,
+            apply_bps_fee(Amount::MAX, DEFAULT_PROTOCOL_FEE_BPS)
+        );
+    }
+
+    #[cfg(feature = "checked-math")]
+    #[test]
+    #[should_panic(expected = "arithmetic overflow")]
+    fn overflowing_gross_panics_under_checked_math() {
+        rebalance_detailed(Amount::MAX, 10_000);
+    }
+
+    /// Outputs captured before the 10_000 / 100 literals became
+    /// `BPS_DENOMINATOR` / `PERCENT_DENOMINATOR`; any drift is a regression.
+    #[test]
+    fn named_denominators_keep_outputs_identical() {
+        const AMOUNTS: [u64; 6] = [0, 1, 99, 10_000, 123_456_789, u64::MAX / 2];
+        let rebalanced: [(u64, [u64; 6]); 4] = [
+            (0, [0, 0, 0, 0, 0, 0]),
+            (
+                2_500,
+                [0, 0, 36, 3_727, 46_014_305, 3_437_697_179_166_860_013],
+            ),
+            (
+                5_000,
+                [0, 0, 73, 7_455, 92_028_609, 6_875_394_358_333_720_026],
+            ),
+            (
+                10_000,
+                [0, 1, 148, 14_909, 184_057_221, 13_750_788_716_667_440_052],
+            ),
+        ];
+        for (throttle_bps, expected) in rebalanced {
+            let got = AMOUNTS.map(|amount| rebalance(amount.into(), throttle_bps).get());
+            assert_eq!(got, expected, "throttle_bps = {throttle_bps}");
+        }
+    }
+
+    #[test]
+    fn report_fields_tie_together() {
+        let report = rebalance_detailed(Amount::new(123_456_789), 4_000);
+        assert_eq!(report.guarded, Amount::new(49_382_715));
+        assert_eq!(report.dex_quote, build_dex_route(report.guarded));
+        assert_eq!(report.lending_quote, build_lending_route(report.guarded));
+        assert_eq!(
+            report.dex_quote.checked_add(report.lending_quote),
+            Some(report.gross)
+        );
+        assert_eq!(
+            report.net,
+            apply_bps_fee(report.gross, DEFAULT_PROTOCOL_FEE_BPS)
+        );
+        assert_eq!(rebalance(Amount::new(123_456_789), 4_000), report.net);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn config_round_trips_through_json() {
+        let cfg = GatewayConfig::new(30, 5_000).unwrap();
+        let json = serde_json::to_string(&cfg).unwrap();
+        assert_eq!(json, r#"{"protocol_fee_bps":30,"throttle_bps":5000}"#);
+        assert_eq!(serde_json::from_str::<GatewayConfig>(&json).unwrap(), cfg);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn config_rejects_unknown_fields() {
+        let typo = r#"{"protocol_fee_bps":30,"throttle_bsp":5000}"#;
+        assert!(serde_json::from_str::<GatewayConfig>(typo).is_err());
+    }
+
+    #[test]
+    fn expired_deadline_is_rejected() {
+        assert_eq!(
+            rebalance_checked_with_deadline(Amount::new(10_000), 5_000, Amount::ZERO, 99, 100),
+            Err(GatewayError::Expired {
+                deadline: 99,
+                now: 100
+            })
+        );
+    }
+
+    #[test]
+    fn unexpired_deadline_matches_rebalance_checked() {
+        for (deadline, now) in [(100, 100), (100, 0)] {
+            assert_eq!(
+                rebalance_checked_with_deadline(
+                    Amount::new(10_000),
+                    5_000,
+                    Amount::ZERO,
+                    deadline,
+                    now
+                ),
+                rebalance_checked(Amount::new(10_000), 5_000, Amount::ZERO)
+            );
+        }
+        assert_eq!(
+            rebalance_checked_with_deadline(Amount::new(10_000), 5_000, Amount::ZERO, 100, 100),
+            Ok(Amount::new(7_455))
+        );
+    }
+
+    #[derive(Default)]
+    struct RecordingObserver {
+        guarded: Vec<Amount>,
+        routes: Vec<(Amount, Amount)>,
+        net: Vec<Amount>,
+    }
+
+    impl RebalanceObserver for RecordingObserver {
+        fn on_guarded(&mut self, amount: Amount) {
+            self.guarded.push(amount);
+        }
+
+        fn on_routes(&mut self, dex: Amount, lending: Amount) {
+            self.routes.push((dex, lending));
+        }
+
+        fn on_net(&mut self, net: Amount) {
+            self.net.push(net);
+        }
+    }
+
+    #[test]
+    fn observer_sees_each_stage_once() {
+        let amount = Amount::new(10_000);
+        let mut obs = RecordingObserver::default();
+        let net = rebalance_observed(amount, 5_000, &mut obs);
+        let report = rebalance_detailed(amount, 5_000);
+        assert_eq!(net, rebalance(amount, 5_000));
+        assert_eq!(obs.guarded, [report.guarded]);
+        assert_eq!(obs.routes, [(report.dex_quote, report.lending_quote)]);
+        assert_eq!(obs.net, [report.net]);
+    }
+
+    #[test]
+    fn zero_amount_is_rejected_at_each_entry_point() {
+        let zero = GatewayError::InvalidAmount(TradeError::ZeroAmount);
+        assert_eq!(validate_amount(Amount::ZERO), Err(TradeError::ZeroAmount));
+        assert_eq!(validate_amount(Amount::new(1)), Ok(Amount::new(1)));
+        assert_eq!(
+            rebalance_checked(Amount::ZERO, 5_000, Amount::ZERO),
+            Err(zero.clone())
+        );
+        assert_eq!(
+            rebalance_checked_with_deadline(Amount::ZERO, 5_000, Amount::ZERO, 100, 100),
+            Err(zero.clone())
+        );
+        assert_eq!(rebalance(Amount::ZERO, 5_000), Amount::ZERO);
+
+        let gateway = Gateway::new(
+            OWNER,
+            GatewayConfig::new(DEFAULT_PROTOCOL_FEE_BPS, 5_000).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            gateway.rebalance(Amount::ZERO, &mut NoopObserver),
+            Err(zero)
+        );
+    }
+
+    #[test]
+    fn preview_matches_direct_rebalance() {
+        let amount = Amount::new(123_456_789);
+        let candidates = [0, 2_500, 5_000, 10_000, 10_001];
+        let preview = rebalance_preview(amount, &candidates);
+        assert_eq!(preview.len(), candidates.len());
+        for (&throttle_bps, &(previewed_bps, net)) in candidates.iter().zip(&preview) {
+            assert_eq!(previewed_bps, throttle_bps);
+            assert_eq!(net, rebalance(amount, throttle_bps));
+        }
+        assert!(rebalance_preview(amount, &[]).is_empty());
+    }
+
+    #[test]
+    fn guarded_route_clamps_before_routing() {
+        for kind in [RouterKind::Dex, RouterKind::Lending, RouterKind::Staking] {
+            for (amount, throttle_bps) in [
+                (0, 5_000),
+                (10_000, 5_000),
+                (123_456_789, 2_500),
+                (u64::MAX, 10_000),
+            ] {
+                assert_eq!(
+                    guarded_route(Amount::new(amount), throttle_bps, kind),
+                    route(kind, ThrottleGuard::new(throttle_bps).clamp(amount).into())
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn out_of_range_configs_are_rejected() {
+        let high_fee = GatewayConfig {
+            protocol_fee_bps: 20_000,
+            throttle_bps: 5_000,
+        };
+        let high_throttle = GatewayConfig {
+            protocol_fee_bps: DEFAULT_PROTOCOL_FEE_BPS,
+            throttle_bps: 10_001,
+        };
+        assert_eq!(GatewayConfig::new(20_000, 5_000), None);
+        assert_eq!(GatewayConfig::new(DEFAULT_PROTOCOL_FEE_BPS, 10_001), None);
+
+        for cfg in [&high_fee, &high_throttle] {
+            assert!(Gateway::new(OWNER, cfg.clone()).is_none());
+            assert_eq!(rebalance_with(Amount::new(10_000), cfg), None);
+            assert_eq!(rebalance_detailed_with(Amount::new(10_000), cfg), None);
+        }
+
+        let initial = GatewayConfig::new(DEFAULT_PROTOCOL_FEE_BPS, 5_000).unwrap();
+        let mut gateway = Gateway::new(OWNER, initial.clone()).unwrap();
+        for cfg in [high_fee, high_throttle] {
+            assert_eq!(
+                gateway.set_config(&OWNER, cfg),
+                Err(GatewayError::InvalidConfig)
+            );
+            assert_eq!(gateway.config(), &initial);
+        }
+    }
+
+    #[test]
+    fn rebalance_with_a_valid_config_matches_rebalance() {
+        let cfg = GatewayConfig::new(DEFAULT_PROTOCOL_FEE_BPS, 5_000).unwrap();
+        assert_eq!(
+            rebalance_with(Amount::new(10_000), &cfg),
+            Some(rebalance(Amount::new(10_000), 5_000))
+        );
+    }
+
+    #[test]
+    fn out_of_range_throttle_is_rejected() {
+        assert_eq!(
+            rebalance_checked(10_000.into(), 10_001, Amount::ZERO),
+            Err(GatewayError::ThrottleRejected)
+        );
+        assert_eq!(
+            rebalance_checked(10_000.into(), 10_000, Amount::ZERO),
+            Ok(rebalance(10_000.into(), 10_000))
+        );
+    }
 
-    apply_bps_fee(dex_quote + lending_quote, 25)
+    #[cfg(feature = "serde")]
+    #[test]
+    fn config_rejects_out_of_range_values() {
+        for manifest in [
+            r#"{"protocol_fee_bps":20000,"throttle_bps":5000}"#,
+            r#"{"protocol_fee_bps":25,"throttle_bps":10001}"#,
+        ] {
+            assert!(serde_json::from_str::<GatewayConfig>(manifest).is_err());
+        }
+    }
 }
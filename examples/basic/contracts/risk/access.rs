@@ -0,0 +1,36 @@
+use super::RiskError;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnerGuard {
+    owner: [u8; 32],
+}
+
+impl OwnerGuard {
+    pub fn new(owner: [u8; 32]) -> Self {
+        Self { owner }
+    }
+
+    pub fn owner(&self) -> &[u8; 32] {
+        &self.owner
+    }
+
+    pub fn require_owner(&self, caller: &[u8; 32]) -> Result<(), RiskError> {
+        if caller != &self.owner {
+            return Err(RiskError::Unauthorized);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_owner_passes() {
+        let guard = OwnerGuard::new([7; 32]);
+        assert_eq!(guard.require_owner(&[7; 32]), Ok(()));
+        assert_eq!(guard.require_owner(&[8; 32]), Err(RiskError::Unauthorized));
+        assert_eq!(guard.owner(), &[7; 32]);
+    }
+}
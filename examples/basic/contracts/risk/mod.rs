@@ -1,7 +1,17 @@
+pub mod access;
+pub mod reentrancy;
 pub mod throttler;
 
+pub use access::OwnerGuard;
+pub use reentrancy::ReentrancyGuard;
 pub use throttler::ThrottleGuard;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RiskError {
+    Reentered,
+    Unauthorized,
+}
+
 pub fn enforce(amount: u64, throttle_bps: u64) -> u64 {
     ThrottleGuard::new(throttle_bps).clamp(amount)
 }
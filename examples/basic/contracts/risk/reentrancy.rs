@@ -0,0 +1,51 @@
+use core::cell::Cell;
+
+use super::RiskError;
+
+#[derive(Debug, Default)]
+pub struct ReentrancyGuard {
+    entered: Cell<bool>,
+}
+
+/// Releases the guard when dropped.
+#[derive(Debug)]
+pub struct ReentrancyToken<'a> {
+    entered: &'a Cell<bool>,
+}
+
+impl ReentrancyGuard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes `&self` so a nested call reaching the same guard gets
+    /// `RiskError::Reentered` instead of a borrow conflict.
+    pub fn enter(&self) -> Result<ReentrancyToken<'_>, RiskError> {
+        if self.entered.replace(true) {
+            return Err(RiskError::Reentered);
+        }
+        Ok(ReentrancyToken {
+            entered: &self.entered,
+        })
+    }
+}
+
+impl Drop for ReentrancyToken<'_> {
+    fn drop(&mut self) {
+        self.entered.set(false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_enter_is_rejected_until_released() {
+        let guard = ReentrancyGuard::new();
+        let token = guard.enter().unwrap();
+        assert_eq!(guard.enter().err(), Some(RiskError::Reentered));
+        drop(token);
+        assert!(guard.enter().is_ok());
+    }
+}
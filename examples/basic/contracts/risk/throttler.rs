@@ -1,14 +1,245 @@
+use crate::math::ratios::basis_points::BPS_DENOMINATOR;
+use crate::math::ratios::{checked_div_floor, MathError};
+
 pub struct ThrottleGuard {
     max_bps: u64,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThrottleError {
+    MissingMaxBps,
+    MaxBpsOutOfRange,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ThrottleGuardBuilder {
+    max_bps: Option<u64>,
+}
+
+impl ThrottleGuardBuilder {
+    pub fn max_bps(mut self, max_bps: u64) -> Self {
+        self.max_bps = Some(max_bps);
+        self
+    }
+
+    pub fn build(self) -> Result<ThrottleGuard, ThrottleError> {
+        let max_bps = self.max_bps.ok_or(ThrottleError::MissingMaxBps)?;
+        if max_bps > BPS_DENOMINATOR {
+            return Err(ThrottleError::MaxBpsOutOfRange);
+        }
+        Ok(ThrottleGuard { max_bps })
+    }
+}
+
 impl ThrottleGuard {
-    pub fn new(max_bps: u64) -> Self {
+    /// Does not validate `max_bps`; values above 10_000 make `try_clamp` fail
+    /// and `clamp` pass amounts through. Prefer `ThrottleGuard::builder`.
+    pub const fn new(max_bps: u64) -> Self {
         Self { max_bps }
     }
 
-    pub fn clamp(&self, amount: u64) -> u64 {
-        let scaled = amount * self.max_bps;
-        scaled / 10_000
+    pub fn builder() -> ThrottleGuardBuilder {
+        ThrottleGuardBuilder::default()
+    }
+
+    /// Falls back to the unclamped `amount` whenever `try_clamp` returns `None`.
+    pub const fn clamp(&self, amount: u64) -> u64 {
+        match self.try_clamp(amount) {
+            Some(clamped) => clamped,
+            None => amount,
+        }
+    }
+
+    /// Returns `None` only when `max_bps > 10_000` (more than 100%); otherwise
+    /// the clamped amount. The `u128` product of two `u64`s cannot overflow and
+    /// the result never exceeds `amount`.
+    pub const fn try_clamp(&self, amount: u64) -> Option<u64> {
+        match self.try_clamp_result(amount) {
+            Ok(clamped) => Some(clamped),
+            Err(_) => None,
+        }
+    }
+
+    /// `try_clamp` reporting the out-of-range `max_bps` as `BpsOutOfRange`.
+    pub const fn try_clamp_result(&self, amount: u64) -> Result<u64, MathError> {
+        if self.max_bps > BPS_DENOMINATOR {
+            return Err(MathError::BpsOutOfRange);
+        }
+        let scaled = amount as u128 * self.max_bps as u128;
+        Ok(checked_div_floor(scaled, BPS_DENOMINATOR as u128)
+            .expect("clamping by at most 10_000 bps fits in the amount"))
+    }
+
+    /// Largest input whose clamped output still fits in `cap - already_used`.
+    pub fn remaining_capacity(&self, already_used: u64, cap: u64) -> u64 {
+        let remaining = cap.saturating_sub(already_used);
+        if self.max_bps == 0 {
+            return u64::MAX;
+        }
+        if self.max_bps > BPS_DENOMINATOR {
+            return remaining;
+        }
+        let bound = (remaining as u128 + 1) * BPS_DENOMINATOR as u128 - 1;
+        checked_div_floor(bound, self.max_bps as u128).unwrap_or(u64::MAX)
+    }
+}
+
+/// Caps the total admitted volume over any `window`-long span of time.
+pub struct WindowedThrottle {
+    window: u64,
+    cap: u64,
+    usage: Vec<(u64, u64)>,
+}
+
+impl WindowedThrottle {
+    pub fn new(window: u64, cap: u64) -> Self {
+        Self {
+            window,
+            cap,
+            usage: Vec::new(),
+        }
+    }
+
+    /// Returns how much of `amount` fits under the cap at `now`, forgetting
+    /// usage recorded `window` or more ticks ago.
+    pub fn admit(&mut self, amount: u64, now: u64) -> u64 {
+        self.usage
+            .retain(|&(recorded_at, _)| now.saturating_sub(recorded_at) < self.window);
+        let used = self
+            .usage
+            .iter()
+            .fold(0u64, |sum, &(_, admitted)| sum.saturating_add(admitted));
+        let allowed = amount.min(self.cap.saturating_sub(used));
+        if allowed > 0 {
+            self.usage.push((now, allowed));
+        }
+        allowed
+    }
+}
+
+/// Token bucket that refills `refill_per_tick` per elapsed tick up to
+/// `capacity`. Starts full.
+pub struct LeakyThrottle {
+    capacity: u64,
+    refill_per_tick: u64,
+    last_tick: u64,
+    available: u64,
+}
+
+impl LeakyThrottle {
+    pub fn new(capacity: u64, refill_per_tick: u64, now: u64) -> Self {
+        Self {
+            capacity,
+            refill_per_tick,
+            last_tick: now,
+            available: capacity,
+        }
+    }
+
+    pub fn available(&self) -> u64 {
+        self.available
+    }
+
+    /// Refills for the ticks since the last call, then spends `amount` only if
+    /// it is fully covered. A `now` earlier than the last tick refills nothing.
+    pub fn try_spend(&mut self, amount: u64, now: u64) -> bool {
+        let elapsed = now.saturating_sub(self.last_tick);
+        let refill = elapsed as u128 * self.refill_per_tick as u128;
+        let refilled = (self.available as u128 + refill).min(self.capacity as u128);
+        self.available = refilled as u64;
+        self.last_tick = self.last_tick.max(now);
+
+        if amount > self.available {
+            return false;
+        }
+        self.available -= amount;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn windowed_throttle_forgets_expired_usage() {
+        let mut throttle = WindowedThrottle::new(10, 100);
+        assert_eq!(throttle.admit(60, 0), 60);
+        assert_eq!(throttle.admit(60, 5), 40);
+        assert_eq!(throttle.admit(10, 9), 0);
+        assert_eq!(throttle.admit(100, 10), 60);
+        assert_eq!(throttle.admit(100, 15), 40);
+    }
+
+    #[test]
+    fn builder_rejects_missing_and_out_of_range_bps() {
+        assert_eq!(
+            ThrottleGuard::builder().build().err(),
+            Some(ThrottleError::MissingMaxBps)
+        );
+        assert_eq!(
+            ThrottleGuard::builder().max_bps(10_001).build().err(),
+            Some(ThrottleError::MaxBpsOutOfRange)
+        );
+        let guard = ThrottleGuard::builder().max_bps(10_000).build().unwrap();
+        assert_eq!(guard.clamp(1_000), 1_000);
+    }
+
+    #[test]
+    fn leaky_throttle_spend_succeeds_after_refill() {
+        let mut bucket = LeakyThrottle::new(100, 10, 0);
+        assert!(bucket.try_spend(80, 0));
+        assert!(!bucket.try_spend(50, 2));
+        assert_eq!(bucket.available(), 40);
+        assert!(bucket.try_spend(50, 3));
+        assert_eq!(bucket.available(), 0);
+    }
+
+    #[test]
+    fn leaky_throttle_refill_caps_without_overflow() {
+        let mut bucket = LeakyThrottle::new(u64::MAX, u64::MAX, 0);
+        assert!(bucket.try_spend(u64::MAX, 0));
+        assert!(bucket.try_spend(u64::MAX, u64::MAX));
+    }
+
+    #[test]
+    fn try_clamp_is_none_only_above_full_bps() {
+        assert_eq!(
+            ThrottleGuard::new(10_000).try_clamp(u64::MAX),
+            Some(u64::MAX)
+        );
+        assert_eq!(ThrottleGuard::new(5_000).try_clamp(1_001), Some(500));
+        assert_eq!(ThrottleGuard::new(10_001).try_clamp(1_000), None);
+        assert_eq!(
+            ThrottleGuard::new(10_001).try_clamp_result(1_000),
+            Err(MathError::BpsOutOfRange)
+        );
+        assert_eq!(ThrottleGuard::new(10_001).clamp(1_000), 1_000);
+    }
+
+    #[test]
+    fn remaining_capacity_saturates_under_every_policy() {
+        assert_eq!(
+            ThrottleGuard::new(1).remaining_capacity(0, u64::MAX),
+            u64::MAX
+        );
+        assert_eq!(ThrottleGuard::new(5_000).remaining_capacity(40, 100), 121);
+    }
+
+    #[test]
+    fn clamp_widens_before_multiplying() {
+        assert_eq!(ThrottleGuard::new(10_000).clamp(u64::MAX), u64::MAX);
+        assert_eq!(ThrottleGuard::new(5_000).clamp(u64::MAX), u64::MAX / 2);
+        assert_eq!(ThrottleGuard::new(1).clamp(u64::MAX), u64::MAX / 10_000);
+        assert_eq!(ThrottleGuard::new(5_000).clamp(10_001), 5_000);
+    }
+
+    #[test]
+    fn clamp_evaluates_in_const_context() {
+        const GUARD: ThrottleGuard = ThrottleGuard::new(5_000);
+        const CLAMPED: u64 = GUARD.clamp(1_000);
+        const REJECTED: Option<u64> = ThrottleGuard::new(10_001).try_clamp(1_000);
+        assert_eq!(CLAMPED, 500);
+        assert_eq!(REJECTED, None);
     }
 }
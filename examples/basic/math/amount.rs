@@ -0,0 +1,110 @@
+use super::ratios::FeeInt;
+
+/// Token amount in base units, taken and returned by the adapter and gateway
+/// entry points. Converts to and from `u64` so existing call sites migrate with
+/// `.into()`, and implements `FeeInt` so the fee and blend helpers accept it
+/// directly. Amounts add and subtract but never multiply one another.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Amount(u64);
+
+impl Amount {
+    pub const ZERO: Amount = Amount(0);
+    pub const MAX: Amount = Amount(u64::MAX);
+
+    pub const fn new(value: u64) -> Self {
+        Self(value)
+    }
+
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+
+    pub const fn checked_add(self, rhs: Self) -> Option<Self> {
+        match self.0.checked_add(rhs.0) {
+            Some(sum) => Some(Self(sum)),
+            None => None,
+        }
+    }
+
+    pub const fn checked_sub(self, rhs: Self) -> Option<Self> {
+        match self.0.checked_sub(rhs.0) {
+            Some(difference) => Some(Self(difference)),
+            None => None,
+        }
+    }
+
+    pub const fn saturating_add(self, rhs: Self) -> Self {
+        Self(self.0.saturating_add(rhs.0))
+    }
+
+    pub const fn saturating_sub(self, rhs: Self) -> Self {
+        Self(self.0.saturating_sub(rhs.0))
+    }
+}
+
+impl From<u64> for Amount {
+    fn from(value: u64) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Amount> for u64 {
+    fn from(amount: Amount) -> Self {
+        amount.0
+    }
+}
+
+impl FeeInt for Amount {
+    const ZERO: Self = Amount(0);
+
+    fn checked_add(self, rhs: Self) -> Option<Self> {
+        Amount::checked_add(self, rhs)
+    }
+
+    fn checked_sub(self, rhs: Self) -> Option<Self> {
+        Amount::checked_sub(self, rhs)
+    }
+
+    fn checked_scale(self, factor: u64) -> Option<Self> {
+        self.0.checked_mul(factor).map(Self)
+    }
+
+    fn checked_div_rem(self, divisor: u64) -> Option<(Self, Self)> {
+        let (quotient, remainder) = self.0.checked_div_rem(divisor)?;
+        Some((Self(quotient), Self(remainder)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::ratios::basis_points::apply_bps_fee;
+    use crate::math::ratios::interpolation::weighted_average;
+
+    #[test]
+    fn helpers_match_raw_u64() {
+        for value in [0, 1, 9_999, 123_456_789, u64::MAX] {
+            let amount = Amount::from(value);
+            assert_eq!(
+                u64::from(apply_bps_fee(amount, 30)),
+                apply_bps_fee(value, 30)
+            );
+            assert_eq!(
+                weighted_average(amount, Amount::new(7), 60).get(),
+                weighted_average(value, 7, 60)
+            );
+        }
+    }
+
+    #[test]
+    fn checked_add_reports_overflow() {
+        assert_eq!(
+            Amount::new(2).checked_add(Amount::new(3)),
+            Some(Amount::new(5))
+        );
+        assert_eq!(Amount::MAX.checked_add(Amount::new(1)), None);
+        assert_eq!(Amount::ZERO.checked_sub(Amount::new(1)), None);
+    }
+}
@@ -0,0 +1,114 @@
+use core::ops::{Div, Mul};
+
+use super::ratios::basis_points::BPS_DENOMINATOR;
+use super::ratios::{checked_div_floor_wide, RoundingMode};
+
+/// Unsigned fixed-point number with nine implied decimal places, so fractional
+/// rates such as 12.5 bps can be represented exactly.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Decimal(u128);
+
+impl Decimal {
+    pub const SCALE: u128 = 1_000_000_000;
+    pub const ZERO: Decimal = Decimal(0);
+    pub const ONE: Decimal = Decimal(Self::SCALE);
+
+    /// Wraps a value already expressed in units of `1 / SCALE`.
+    pub const fn from_raw(raw: u128) -> Self {
+        Self(raw)
+    }
+
+    pub const fn raw(self) -> u128 {
+        self.0
+    }
+
+    /// `bps / 10_000`, e.g. `from_bps(25)` is 0.0025.
+    pub const fn from_bps(bps: u64) -> Self {
+        let per_bps = checked_div_floor_wide(Self::SCALE, BPS_DENOMINATOR as u128)
+            .expect("BPS_DENOMINATOR is nonzero");
+        Self(bps as u128 * per_bps)
+    }
+
+    /// `numerator / denominator` rounded down to the nearest `1 / SCALE`.
+    /// Returns `None` when `denominator == 0` or the result overflows.
+    pub const fn from_ratio(numerator: u128, denominator: u128) -> Option<Self> {
+        match numerator.checked_mul(Self::SCALE) {
+            Some(scaled) => Self::wrap(checked_div_floor_wide(scaled, denominator)),
+            None => None,
+        }
+    }
+
+    /// Product rounded down to the nearest `1 / SCALE`.
+    pub const fn checked_mul(self, rhs: Self) -> Option<Self> {
+        match self.0.checked_mul(rhs.0) {
+            Some(product) => Self::wrap(checked_div_floor_wide(product, Self::SCALE)),
+            None => None,
+        }
+    }
+
+    /// Quotient rounded down to the nearest `1 / SCALE`. Returns `None` when
+    /// `rhs` is zero or the result overflows.
+    pub const fn checked_div(self, rhs: Self) -> Option<Self> {
+        match self.0.checked_mul(Self::SCALE) {
+            Some(scaled) => Self::wrap(checked_div_floor_wide(scaled, rhs.0)),
+            None => None,
+        }
+    }
+
+    const fn wrap(raw: Option<u128>) -> Option<Self> {
+        match raw {
+            Some(raw) => Some(Self(raw)),
+            None => None,
+        }
+    }
+
+    pub const fn round_to_integer(self, mode: RoundingMode) -> u128 {
+        mode.divide(self.0, Self::SCALE)
+    }
+}
+
+/// Panics on overflow; use `Decimal::checked_mul` to handle it.
+impl Mul for Decimal {
+    type Output = Decimal;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.checked_mul(rhs)
+            .expect("decimal multiplication overflowed")
+    }
+}
+
+/// Panics on overflow or a zero divisor; use `Decimal::checked_div` to handle
+/// them.
+impl Div for Decimal {
+    type Output = Decimal;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        self.checked_div(rhs).expect("decimal division failed")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::ratios::basis_points::apply_fee_decimal;
+
+    #[test]
+    fn twelve_and_a_half_bps_fee_on_a_round_amount() {
+        let fee = Decimal::from_bps(25) / Decimal::from_raw(2 * Decimal::SCALE);
+        assert_eq!(Decimal::from_ratio(125, 100_000), Some(fee));
+        assert_eq!(apply_fee_decimal(1_000_000, fee), 998_750);
+        let charged = Decimal::from_raw(1_000_000 * Decimal::SCALE) * fee;
+        assert_eq!(charged.round_to_integer(RoundingMode::Floor), 1_250);
+    }
+
+    #[test]
+    fn round_to_integer_follows_the_mode() {
+        let half = Decimal::from_ratio(5, 2).unwrap();
+        assert_eq!(half.round_to_integer(RoundingMode::Floor), 2);
+        assert_eq!(half.round_to_integer(RoundingMode::Ceil), 3);
+        assert_eq!(half.round_to_integer(RoundingMode::HalfUp), 3);
+        assert_eq!(Decimal::ONE.checked_div(Decimal::ZERO), None);
+    }
+}
@@ -0,0 +1,4 @@
+pub mod amount;
+pub mod decimal;
+pub mod oracle;
+pub mod ratios;
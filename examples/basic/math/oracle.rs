@@ -0,0 +1,81 @@
+use super::ratios::checked_div_floor;
+
+/// Records `(price, timestamp)` observations and averages them over time. Each
+/// price is treated as holding until the next observation (or `now`).
+#[derive(Debug, Clone, Default)]
+pub struct TwapAccumulator {
+    max_window: u64,
+    observations: Vec<(u64, u64)>,
+}
+
+impl TwapAccumulator {
+    /// Observations older than `max_window` are pruned as new ones arrive.
+    pub fn new(max_window: u64) -> Self {
+        Self {
+            max_window,
+            observations: Vec::new(),
+        }
+    }
+
+    /// Ignores observations timestamped before the latest recorded one.
+    pub fn observe(&mut self, price: u64, now: u64) {
+        if self.observations.last().is_some_and(|&(_, at)| now < at) {
+            return;
+        }
+        let cutoff = now.saturating_sub(self.max_window);
+        self.observations.retain(|&(_, at)| at >= cutoff);
+        self.observations.push((price, now));
+    }
+
+    /// Time-weighted average of the observations within `window` of `now`.
+    /// Returns `None` with fewer than two such observations or when they span
+    /// no time.
+    pub fn twap(&self, window: u64, now: u64) -> Option<u64> {
+        let cutoff = now.saturating_sub(window);
+        let start = self.observations.partition_point(|&(_, at)| at < cutoff);
+        let end = self.observations.partition_point(|&(_, at)| at <= now);
+        let points = &self.observations[start..end.max(start)];
+        if points.len() < 2 {
+            return None;
+        }
+
+        let mut weighted: u128 = 0;
+        for (idx, &(price, at)) in points.iter().enumerate() {
+            let until = points.get(idx + 1).map_or(now, |&(_, next)| next);
+            weighted += price as u128 * (until - at) as u128;
+        }
+        checked_div_floor(weighted, (now - points[0].1) as u128)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn twap_weights_prices_by_duration() {
+        let mut acc = TwapAccumulator::new(100);
+        acc.observe(100, 0);
+        assert_eq!(acc.twap(100, 20), None);
+        acc.observe(200, 10);
+        assert_eq!(acc.twap(100, 20), Some(150));
+        assert_eq!(acc.twap(100, 10), Some(100));
+    }
+
+    #[test]
+    fn observations_outside_the_window_are_pruned() {
+        let mut acc = TwapAccumulator::new(100);
+        acc.observe(100, 0);
+        acc.observe(200, 10);
+        acc.observe(300, 200);
+        assert_eq!(acc.twap(1_000, 250), None);
+    }
+
+    #[test]
+    fn observations_spanning_no_time_have_no_twap() {
+        let mut acc = TwapAccumulator::new(100);
+        acc.observe(100, 5);
+        acc.observe(200, 5);
+        assert_eq!(acc.twap(10, 5), None);
+    }
+}
@@ -1,7 +1,515 @@
-pub fn apply_bps_fee(amount: u64, bps: u64) -> u64 {
-    if bps == 0 {
-        return amount;
+use super::{checked_div_floor, mul_div_floor, FeeInt, MathError, RoundingMode};
+use crate::math::decimal::Decimal;
+extern crate alloc;
+use alloc::vec::Vec;
+
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Deserializes through `TryFrom<u64>`, so out-of-range values are rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "u64", into = "u64"))]
+pub struct Bps(u64);
+
+impl Bps {
+    pub const MAX: Bps = Bps(BPS_DENOMINATOR);
+
+    /// Returns `None` for values above `Bps::MAX`.
+    pub const fn new(value: u64) -> Option<Self> {
+        if value > Self::MAX.0 {
+            return None;
+        }
+        Some(Self(value))
+    }
+
+    pub const fn as_u64(self) -> u64 {
+        self.0
+    }
+}
+
+impl TryFrom<u64> for Bps {
+    type Error = MathError;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        Self::new(value).ok_or(MathError::BpsOutOfRange)
+    }
+}
+
+impl From<Bps> for u64 {
+    fn from(bps: Bps) -> Self {
+        bps.0
+    }
+}
+
+/// Returns `amount` net of a `bps` fee, with the fee rounded down. Saturates at
+/// a full-amount fee (returns 0) when `bps` is not a valid `Bps` or equals
+/// `Bps::MAX`.
+pub fn apply_bps_fee<T: FeeInt>(amount: T, bps: impl TryInto<Bps>) -> T {
+    bps.try_into()
+        .ok()
+        .and_then(|bps: Bps| net_of_bps(amount, bps))
+        .unwrap_or(T::ZERO)
+}
+
+fn net_of_bps<T: FeeInt>(amount: T, bps: Bps) -> Option<T> {
+    let fee = mul_div_floor(amount, bps.as_u64(), BPS_DENOMINATOR)?;
+    debug_assert!(fee <= amount);
+    amount.checked_sub(fee)
+}
+
+/// Returns `None` when `bps > 10_000` or the fee computation overflows.
+pub const fn checked_apply_bps_fee(amount: u64, bps: u64) -> Option<u64> {
+    match try_apply_bps_fee(amount, bps) {
+        Ok(net) => Some(net),
+        Err(_) => None,
+    }
+}
+
+/// Returns `BpsOutOfRange` when `bps > 10_000` and `Overflow` when the fee
+/// computation overflows.
+pub const fn try_apply_bps_fee(amount: u64, bps: u64) -> Result<u64, MathError> {
+    match Bps::new(bps) {
+        Some(bps) => try_apply_fee_with_denominator(amount, bps.as_u64(), Bps::MAX.as_u64()),
+        None => Err(MathError::BpsOutOfRange),
+    }
+}
+
+/// Returns `amount` net of a `numerator / denominator` fee (rounded down), or
+/// `None` when `denominator == 0` or `numerator > denominator`.
+pub const fn apply_fee_with_denominator(
+    amount: u64,
+    numerator: u64,
+    denominator: u64,
+) -> Option<u64> {
+    match try_apply_fee_with_denominator(amount, numerator, denominator) {
+        Ok(net) => Some(net),
+        Err(_) => None,
+    }
+}
+
+/// `amount * numerator` cannot overflow a `u128` and the fee never exceeds
+/// `amount`, so the only failures are the argument checks.
+pub const fn try_apply_fee_with_denominator(
+    amount: u64,
+    numerator: u64,
+    denominator: u64,
+) -> Result<u64, MathError> {
+    if denominator == 0 {
+        return Err(MathError::DivideByZero);
+    }
+    if numerator > denominator {
+        return Err(MathError::OutOfRange);
+    }
+    let fee = checked_div_floor(amount as u128 * numerator as u128, denominator as u128)
+        .expect("a nonzero denominator no smaller than the numerator keeps the fee in range");
+    debug_assert!(fee <= amount);
+    Ok(amount - fee)
+}
+
+/// Like `apply_bps_fee` but rounds the fee itself according to `mode`; a zero
+/// `bps` never produces a fee. Returns 0 when `bps > 10_000`.
+pub const fn apply_bps_fee_rounded(amount: u64, bps: u64, mode: RoundingMode) -> u64 {
+    if bps > Bps::MAX.as_u64() {
+        return 0;
+    }
+    let fee = mode.divide(amount as u128 * bps as u128, BPS_DENOMINATOR as u128);
+    debug_assert!(fee <= amount as u128);
+    (amount as u128 - fee) as u64
+}
+
+/// Returns `amount` net of a fractional `fee` rate, with the fee rounded down.
+/// A rate above `Decimal::ONE` takes the full amount, as in `apply_bps_fee`.
+pub const fn apply_fee_decimal(amount: u64, fee: Decimal) -> u64 {
+    if fee.raw() > Decimal::ONE.raw() {
+        return 0;
+    }
+    let charged = checked_div_floor(amount as u128 * fee.raw(), Decimal::SCALE)
+        .expect("a fee of at most Decimal::ONE fits in the amount");
+    amount - charged
+}
+
+/// Fee owed back when only `filled_amount` of `original_amount` executed: the
+/// fee on the original minus the fee on the filled part, each rounded down.
+/// Returns 0 when `filled_amount >= original_amount`. A `bps` above 10_000 is
+/// treated as a full-amount fee, as in `apply_bps_fee`.
+pub const fn fee_refund(original_amount: u64, filled_amount: u64, bps: u64) -> u64 {
+    if filled_amount >= original_amount {
+        return 0;
+    }
+    let bps = if bps > Bps::MAX.as_u64() {
+        Bps::MAX.as_u64()
+    } else {
+        bps
+    };
+    let original_fee = bps_fee_floor(original_amount, bps);
+    let filled_fee = bps_fee_floor(filled_amount, bps);
+    debug_assert!(filled_fee <= original_fee);
+    original_fee - filled_fee
+}
+
+/// Smallest `gross` such that `apply_bps_fee(gross, bps) >= net`. Returns
+/// `None` when `bps >= 10_000` or the result does not fit in a `u64`.
+pub const fn gross_from_net(net: u64, bps: u64) -> Option<u64> {
+    if bps >= Bps::MAX.as_u64() {
+        return None;
+    }
+    if net == 0 {
+        return Some(0);
+    }
+    let kept = (Bps::MAX.as_u64() - bps) as u128;
+    match checked_div_floor((net as u128 - 1) * Bps::MAX.as_u64() as u128, kept) {
+        Some(quotient) => quotient.checked_add(1),
+        None => None,
+    }
+}
+
+/// Fee on `amount` at `bps <= 10_000`, rounded down.
+const fn bps_fee_floor(amount: u64, bps: u64) -> u64 {
+    checked_div_floor(amount as u128 * bps as u128, BPS_DENOMINATOR as u128)
+        .expect("a fee of at most 10_000 bps fits in the amount")
+}
+
+/// Applies each fee in `fees` in order, rounding down after every step so the
+/// result matches chaining `checked_apply_bps_fee` by hand. Returns `None` if
+/// any fee exceeds 10_000 bps.
+pub fn apply_bps_fees_compounded(amount: u64, fees: &[u64]) -> Option<u64> {
+    fees.iter()
+        .try_fold(amount, |net, &bps| checked_apply_bps_fee(net, bps))
+}
+
+/// Validates `bps` once and returns `None` if it is out of range or any
+/// element overflows. Use `apply_bps_fee_batch_mut` to avoid the allocation.
+pub fn apply_bps_fee_batch(amounts: &[u64], bps: u64) -> Option<Vec<u64>> {
+    let bps = Bps::new(bps)?;
+    amounts
+        .iter()
+        .map(|&amount| net_of_bps(amount, bps))
+        .collect()
+}
+
+/// In-place `apply_bps_fee_batch`. Returns `None` without touching `amounts`
+/// when `bps` is out of range; an overflowing element stops the pass there.
+pub fn apply_bps_fee_batch_mut(amounts: &mut [u64], bps: u64) -> Option<()> {
+    let bps = Bps::new(bps)?;
+    for amount in amounts.iter_mut() {
+        *amount = net_of_bps(*amount, bps)?;
+    }
+    Some(())
+}
+
+/// Takes a `bps` fee capped at `max_fee`. An out-of-range `bps` is treated as a
+/// full-amount fee before the cap applies, as in `apply_bps_fee`.
+pub fn apply_bps_fee_capped(amount: u64, bps: u64, max_fee: u64) -> u64 {
+    let fee = match Bps::new(bps) {
+        Some(bps) => amount - net_of_bps(amount, bps).unwrap_or(0),
+        None => amount,
+    };
+    amount - fee.min(max_fee)
+}
+
+/// Fee schedule of `(min_amount, bps)` brackets sorted by `min_amount`.
+/// Deserializes from the bare tier list through `TieredFee::new`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(try_from = "Vec<(u64, u64)>", into = "Vec<(u64, u64)>")
+)]
+pub struct TieredFee {
+    tiers: Vec<(u64, u64)>,
+}
+
+impl TieredFee {
+    /// Returns `None` when `tiers` is empty, not strictly ascending by
+    /// `min_amount`, or contains a `bps` above 10_000.
+    pub fn new(tiers: Vec<(u64, u64)>) -> Option<Self> {
+        if tiers.is_empty() {
+            return None;
+        }
+        let ascending = tiers.windows(2).all(|pair| pair[0].0 < pair[1].0);
+        let in_range = tiers.iter().all(|&(_, bps)| Bps::new(bps).is_some());
+        if !ascending || !in_range {
+            return None;
+        }
+        Some(Self { tiers })
+    }
+
+    pub fn tiers(&self) -> &[(u64, u64)] {
+        &self.tiers
+    }
+
+    /// Bps of the highest bracket with `min_amount <= amount`; 0 when `amount`
+    /// is below every bracket.
+    pub fn fee_bps_for(&self, amount: u64) -> u64 {
+        self.tiers
+            .iter()
+            .rev()
+            .find(|&&(min_amount, _)| min_amount <= amount)
+            .map_or(0, |&(_, bps)| bps)
+    }
+
+    pub fn apply(&self, amount: u64) -> u64 {
+        apply_bps_fee(amount, self.fee_bps_for(amount))
+    }
+}
+
+impl TryFrom<Vec<(u64, u64)>> for TieredFee {
+    type Error = MathError;
+
+    fn try_from(tiers: Vec<(u64, u64)>) -> Result<Self, Self::Error> {
+        Self::new(tiers).ok_or(MathError::OutOfRange)
+    }
+}
+
+impl From<TieredFee> for Vec<(u64, u64)> {
+    fn from(fee: TieredFee) -> Self {
+        fee.tiers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_matches_mut_variant() {
+        let mut amounts = [0, 1, 10_000, u64::MAX];
+        let batched = apply_bps_fee_batch(&amounts, 30).unwrap();
+        assert_eq!(apply_bps_fee_batch_mut(&mut amounts, 30), Some(()));
+        assert_eq!(batched, amounts);
+        assert_eq!(apply_bps_fee_batch(&amounts, 10_001), None);
+    }
+
+    #[test]
+    fn tiered_fee_picks_highest_matching_bracket() {
+        let fee = TieredFee::new(vec![(1_000, 30), (100_000, 10)]).unwrap();
+        assert_eq!(fee.fee_bps_for(999), 0);
+        assert_eq!(fee.apply(999), 999);
+        assert_eq!(fee.fee_bps_for(1_000), 30);
+        assert_eq!(fee.apply(10_000), 9_970);
+        assert_eq!(fee.fee_bps_for(100_000), 10);
+        assert_eq!(fee.apply(100_000), 99_900);
+    }
+
+    #[test]
+    fn tiered_fee_rejects_invalid_schedules() {
+        assert_eq!(TieredFee::new(vec![]), None);
+        assert_eq!(TieredFee::new(vec![(10, 5), (10, 6)]), None);
+        assert_eq!(TieredFee::new(vec![(0, 10_001)]), None);
+    }
+
+    #[test]
+    fn batch_mut_leaves_amounts_on_invalid_bps() {
+        let mut amounts = [10_000, 20_000];
+        assert_eq!(apply_bps_fee_batch_mut(&mut amounts, 10_001), None);
+        assert_eq!(amounts, [10_000, 20_000]);
+        assert_eq!(apply_bps_fee_batch_mut(&mut amounts, 30), Some(()));
+        assert_eq!(amounts, [9_970, 19_940]);
+    }
+
+    #[test]
+    fn apply_bps_fee_pins_net_of_fee_outputs() {
+        assert_eq!(apply_bps_fee(10_000u64, 30), 9_970);
+        assert_eq!(apply_bps_fee(123_456_789u64, 25), 123_148_148);
+        assert_eq!(apply_bps_fee(1u64, 9_999), 1);
+        assert_eq!(apply_bps_fee(u64::MAX, 0), u64::MAX);
+        assert_eq!(apply_bps_fee(u64::MAX, 10_000), 0);
+        assert_eq!(apply_bps_fee(10_000u64, 10_001), 0);
+        assert_eq!(apply_bps_fee(u128::MAX, 1), u128::MAX - u128::MAX / 10_000);
+    }
+
+    #[test]
+    fn rounding_modes_differ_by_one_unit() {
+        assert_eq!(
+            apply_bps_fee_rounded(10_001, 30, RoundingMode::Floor),
+            9_971
+        );
+        assert_eq!(apply_bps_fee_rounded(10_001, 30, RoundingMode::Ceil), 9_970);
+        assert_eq!(
+            apply_bps_fee_rounded(10_001, 30, RoundingMode::NearestEven),
+            9_971
+        );
+        assert_eq!(
+            apply_bps_fee_rounded(10_001, 30, RoundingMode::Floor),
+            apply_bps_fee(10_001u64, 30)
+        );
+    }
+
+    #[test]
+    fn ceil_never_charges_a_zero_bps_fee() {
+        assert_eq!(
+            apply_bps_fee_rounded(u64::MAX, 0, RoundingMode::Ceil),
+            u64::MAX
+        );
+        assert_eq!(apply_bps_fee_rounded(1, 0, RoundingMode::Ceil), 1);
+    }
+
+    #[test]
+    fn nearest_even_breaks_ties_to_even() {
+        assert_eq!(
+            apply_bps_fee_rounded(5_000, 1, RoundingMode::NearestEven),
+            5_000
+        );
+        assert_eq!(
+            apply_bps_fee_rounded(15_000, 1, RoundingMode::NearestEven),
+            14_998
+        );
+        assert_eq!(apply_bps_fee_rounded(5_000, 1, RoundingMode::HalfUp), 4_999);
+    }
+
+    #[test]
+    fn gross_from_net_round_trips() {
+        for net in [0, 1, 2, 9_970, 123_456_789, 1_000_000_007] {
+            for bps in [0, 1, 30, 5_000, 9_999] {
+                let gross = gross_from_net(net, bps).unwrap();
+                assert!(apply_bps_fee(gross, bps) >= net, "net = {net}, bps = {bps}");
+                assert!(gross == 0 || apply_bps_fee(gross - 1, bps) < net);
+            }
+        }
+    }
+
+    #[test]
+    fn gross_from_net_rejects_full_fee_and_overflow() {
+        assert_eq!(gross_from_net(1, 10_000), None);
+        assert_eq!(gross_from_net(1, 10_001), None);
+        assert_eq!(gross_from_net(u64::MAX, 30), None);
+    }
+
+    #[test]
+    fn compounded_fees_round_per_step() {
+        let amount = 123_456_789;
+        let stepwise = apply_bps_fee(apply_bps_fee(amount, 80), 25);
+        assert_eq!(apply_bps_fees_compounded(amount, &[80, 25]), Some(stepwise));
+        assert_eq!(apply_bps_fees_compounded(amount, &[]), Some(amount));
+        assert_eq!(apply_bps_fees_compounded(amount, &[30, 10_001]), None);
+    }
+
+    #[test]
+    fn capped_fee_binds_only_above_the_cap() {
+        assert_eq!(apply_bps_fee_capped(10_000, 30, 10), 9_990);
+        assert_eq!(apply_bps_fee_capped(10_000, 30, 100), 9_970);
+        assert_eq!(apply_bps_fee_capped(10_000, 30, 30), 9_970);
+        assert_eq!(
+            apply_bps_fee_capped(u64::MAX, 30, u64::MAX),
+            apply_bps_fee(u64::MAX, 30)
+        );
+    }
+
+    #[test]
+    fn zero_cap_takes_no_fee() {
+        for bps in [0, 30, 10_000, 10_001] {
+            assert_eq!(apply_bps_fee_capped(10_000, bps, 0), 10_000);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn bps_deserializes_only_in_range() {
+        assert_eq!(serde_json::to_string(&Bps::MAX).unwrap(), "10000");
+        assert_eq!(
+            serde_json::from_str::<Bps>("30").unwrap(),
+            Bps::new(30).unwrap()
+        );
+        assert!(serde_json::from_str::<Bps>("10001").is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn tiered_fee_round_trips_through_json() {
+        let fee = TieredFee::new(vec![(0, 30), (1_000, 10)]).unwrap();
+        let json = serde_json::to_string(&fee).unwrap();
+        assert_eq!(json, "[[0,30],[1000,10]]");
+        assert_eq!(serde_json::from_str::<TieredFee>(&json).unwrap(), fee);
+        assert!(serde_json::from_str::<TieredFee>("[[10,30],[10,10]]").is_err());
+    }
+
+    #[test]
+    fn fee_helpers_evaluate_in_const_context() {
+        const NET: Option<u64> = checked_apply_bps_fee(10_000, 30);
+        const ROUNDED_UP: u64 = apply_bps_fee_rounded(10_001, 30, RoundingMode::Ceil);
+        const GROSS: Option<u64> = gross_from_net(9_970, 30);
+        const REFUND: u64 = fee_refund(10_000, 4_000, 30);
+        assert_eq!(NET, Some(9_970));
+        assert_eq!(ROUNDED_UP, 9_970);
+        assert_eq!(GROSS, Some(9_999));
+        assert_eq!(REFUND, 18);
+    }
+
+    #[test]
+    fn fee_refund_covers_full_and_half_fills() {
+        assert_eq!(fee_refund(10_000, 10_000, 30), 0);
+        assert_eq!(fee_refund(10_000, 12_000, 30), 0);
+        assert_eq!(fee_refund(10_000, 5_000, 30), 15);
+        assert_eq!(fee_refund(10_000, 0, 30), 30);
+        assert_eq!(fee_refund(u64::MAX, 0, 20_000), u64::MAX);
+    }
+
+    #[test]
+    fn fee_never_exceeds_the_amount() {
+        for amount in [0, 1, 9_999, 10_000, u64::MAX / 2, u64::MAX] {
+            for bps in [0, 1, 30, 9_999, 10_000] {
+                assert!(apply_bps_fee(amount, bps) <= amount);
+                assert!(apply_bps_fee(amount as u128, bps) <= amount as u128);
+                for mode in [
+                    RoundingMode::Floor,
+                    RoundingMode::Ceil,
+                    RoundingMode::HalfUp,
+                ] {
+                    assert!(apply_bps_fee_rounded(amount, bps, mode) <= amount);
+                }
+                assert!(fee_refund(amount, amount / 2, bps) <= amount);
+            }
+        }
+    }
+
+    #[test]
+    fn ppm_denominator_expresses_sub_bps_fees() {
+        assert_eq!(
+            apply_fee_with_denominator(1_000_000, 30, 1_000_000),
+            Some(999_970)
+        );
+        assert_eq!(
+            apply_fee_with_denominator(1_000_000, 1_000_000, 1_000_000),
+            Some(0)
+        );
+        assert_eq!(
+            apply_fee_with_denominator(1_000_000, 1_000_001, 1_000_000),
+            None
+        );
+        assert_eq!(
+            try_apply_fee_with_denominator(1_000_000, 1_000_001, 1_000_000),
+            Err(MathError::OutOfRange)
+        );
+        assert_eq!(
+            apply_fee_with_denominator(u64::MAX, 30, 10_000),
+            checked_apply_bps_fee(u64::MAX, 30)
+        );
+    }
+
+    /// Outputs captured before the 10_000 literal became `BPS_DENOMINATOR`; any
+    /// drift is a regression.
+    #[test]
+    fn named_denominators_keep_outputs_identical() {
+        const AMOUNTS: [u64; 6] = [0, 1, 99, 10_000, 123_456_789, u64::MAX / 2];
+        let fees: [(u64, [u64; 6]); 5] = [
+            (
+                0,
+                [0, 1, 99, 10_000, 123_456_789, 9_223_372_036_854_775_807],
+            ),
+            (1, [0, 1, 99, 9_999, 123_444_444, 9_222_449_699_651_090_330]),
+            (
+                30,
+                [0, 1, 99, 9_970, 123_086_419, 9_195_701_920_744_211_480],
+            ),
+            (9_999, [0, 1, 1, 1, 12_346, 922_337_203_685_478]),
+            (10_000, [0, 0, 0, 0, 0, 0]),
+        ];
+        for (bps, expected) in fees {
+            assert_eq!(
+                AMOUNTS.map(|amount| apply_bps_fee(amount, bps)),
+                expected,
+                "bps = {bps}"
+            );
+        }
     }
-    let fee = amount.saturating_mul(bps) / 10_000;
-    amount.saturating_sub(fee)
 }
@@ -1,4 +1,454 @@
-pub fn weighted_average(a: u64, b: u64, weight_a: u64) -> u64 {
-    let weight_b = 100 - weight_a;
-    ((a * weight_a) + (b * weight_b)) / 100
+use super::basis_points::BPS_DENOMINATOR;
+use super::{checked_div_floor, checked_div_floor_wide, FeeInt, MathError, RoundingMode};
+extern crate alloc;
+use alloc::vec::Vec;
+
+pub const PERCENT_DENOMINATOR: u64 = 100;
+
+/// Panics when `try_weighted_average` returns an error.
+pub fn weighted_average<T: FeeInt>(a: T, b: T, weight_a: u64) -> T {
+    debug_assert!(weight_a <= PERCENT_DENOMINATOR);
+    match try_weighted_average(a, b, weight_a) {
+        Ok(value) => value,
+        Err(err) => panic!("weighted_average failed: {err:?}"),
+    }
+}
+
+/// `try_weighted_average` on `u64`, the blend-side counterpart of
+/// `try_apply_bps_fee`.
+pub fn checked_weighted_average(a: u64, b: u64, weight_a: u64) -> Result<u64, MathError> {
+    try_weighted_average(a, b, weight_a)
+}
+
+/// Returns `WeightOutOfRange` when `weight_a > 100` and `Overflow` when the
+/// blend does not fit in `T`. Each input is split into `q * 100 + r` so the
+/// products never need a wider type.
+pub fn try_weighted_average<T: FeeInt>(a: T, b: T, weight_a: u64) -> Result<T, MathError> {
+    if weight_a > PERCENT_DENOMINATOR {
+        return Err(MathError::WeightOutOfRange);
+    }
+    let weight_b = PERCENT_DENOMINATOR - weight_a;
+    let blend = || {
+        let (a_whole, a_part) = a.checked_div_rem(PERCENT_DENOMINATOR)?;
+        let (b_whole, b_part) = b.checked_div_rem(PERCENT_DENOMINATOR)?;
+        let whole = a_whole
+            .checked_scale(weight_a)?
+            .checked_add(b_whole.checked_scale(weight_b)?)?;
+        let (part, _) = a_part
+            .checked_scale(weight_a)?
+            .checked_add(b_part.checked_scale(weight_b)?)?
+            .checked_div_rem(PERCENT_DENOMINATOR)?;
+        whole.checked_add(part)
+    };
+    let blended = blend().ok_or(MathError::Overflow)?;
+    let (low, high) = if a <= b { (a, b) } else { (b, a) };
+    debug_assert!(low <= blended && blended <= high);
+    Ok(blended)
+}
+
+/// Blend with `weight_a_bps` in basis points, for weights finer than a whole
+/// percent (e.g. 6_250 for 62.5%). Rounds down. Returns `None` when
+/// `weight_a_bps > 10_000`.
+pub const fn weighted_average_bps(a: u64, b: u64, weight_a_bps: u64) -> Option<u64> {
+    if weight_a_bps > BPS_DENOMINATOR {
+        return None;
+    }
+    let blended =
+        a as u128 * weight_a_bps as u128 + b as u128 * (BPS_DENOMINATOR - weight_a_bps) as u128;
+    checked_div_floor(blended, BPS_DENOMINATOR as u128)
+}
+
+/// `weighted_average` that refuses to blend inputs more than
+/// `max_deviation_bps` apart, relative to the smaller one, so a single
+/// manipulated input cannot dominate. Returns `None` on too wide a gap (any gap
+/// from a zero input counts) or when `weight_a > 100`.
+pub fn weighted_average_bounded(
+    a: u64,
+    b: u64,
+    weight_a: u64,
+    max_deviation_bps: u64,
+) -> Option<u64> {
+    let (low, high) = if a <= b { (a, b) } else { (b, a) };
+    let gap = (high - low) as u128 * BPS_DENOMINATOR as u128;
+    if gap > low as u128 * max_deviation_bps as u128 {
+        return None;
+    }
+    try_weighted_average(a, b, weight_a).ok()
+}
+
+/// `weighted_average` with an explicit rounding mode; `HalfUp` removes the
+/// downward drift of repeated floor blends. Returns `WeightOutOfRange` when
+/// `weight_a > 100`.
+pub fn weighted_average_rounded(
+    a: u64,
+    b: u64,
+    weight_a: u64,
+    mode: RoundingMode,
+) -> Result<u64, MathError> {
+    if weight_a > PERCENT_DENOMINATOR {
+        return Err(MathError::WeightOutOfRange);
+    }
+    let blended =
+        a as u128 * weight_a as u128 + b as u128 * (PERCENT_DENOMINATOR - weight_a) as u128;
+    Ok(mode.divide(blended, PERCENT_DENOMINATOR as u128) as u64)
+}
+
+/// Blends `(value, weight)` pairs. Returns `None` when `values` is empty, the
+/// weights do not sum to exactly 100, or the blend does not fit in a `u64`.
+pub fn weighted_average_n(values: &[(u64, u64)]) -> Option<u64> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut total_weight: u128 = 0;
+    let mut blended: u128 = 0;
+    for &(value, weight) in values {
+        total_weight = total_weight.checked_add(weight as u128)?;
+        blended = blended.checked_add((value as u128).checked_mul(weight as u128)?)?;
+    }
+    if total_weight != PERCENT_DENOMINATOR as u128 {
+        return None;
+    }
+    checked_div_floor(blended, PERCENT_DENOMINATOR as u128)
+}
+
+/// Signed blend of `a` and `b`, rounded toward negative infinity so it stays
+/// consistent with the unsigned floor (e.g. blending -1 and 0 at 50 gives -1,
+/// not 0). Returns `None` when `weight_a > 100` or the products overflow.
+pub fn weighted_average_signed(a: i128, b: i128, weight_a: u64) -> Option<i128> {
+    if weight_a > PERCENT_DENOMINATOR {
+        return None;
+    }
+    let weight_b = (PERCENT_DENOMINATOR - weight_a) as i128;
+    let blended = a
+        .checked_mul(weight_a as i128)?
+        .checked_add(b.checked_mul(weight_b)?)?;
+    Some(blended.div_euclid(PERCENT_DENOMINATOR as i128))
+}
+
+pub const fn signed_net(inflow: u64, outflow: u64) -> i128 {
+    inflow as i128 - outflow as i128
+}
+
+/// y-value at `x` on the line through `(x0, y0)` and `(x1, y1)`, with `x`
+/// clamped to the segment. The offset from the left point's y is rounded
+/// down, so results lean toward that endpoint. Returns `None` when `x0 == x1`.
+pub fn lerp(x0: u64, y0: u64, x1: u64, y1: u64, x: u64) -> Option<u64> {
+    if x0 == x1 {
+        return None;
+    }
+    let ((x0, y0), (x1, y1)) = if x0 < x1 {
+        ((x0, y0), (x1, y1))
+    } else {
+        ((x1, y1), (x0, y0))
+    };
+    let x = x.clamp(x0, x1);
+    let dx = (x - x0) as u128;
+    let span = (x1 - x0) as u128;
+    let value = if y1 >= y0 {
+        y0 as u128 + checked_div_floor_wide((y1 - y0) as u128 * dx, span)?
+    } else {
+        y0 as u128 - checked_div_floor_wide((y0 - y1) as u128 * dx, span)?
+    };
+    debug_assert!(value >= y0.min(y1) as u128 && value <= y0.max(y1) as u128);
+    Some(value as u64)
+}
+
+/// Piecewise-linear curve through breakpoints sorted strictly by x.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Curve {
+    points: Vec<(u64, u64)>,
+}
+
+impl Curve {
+    /// Returns `None` when `points` is empty or not strictly increasing in x.
+    pub fn new(points: Vec<(u64, u64)>) -> Option<Self> {
+        if points.is_empty() || !points.windows(2).all(|pair| pair[0].0 < pair[1].0) {
+            return None;
+        }
+        Some(Self { points })
+    }
+
+    pub fn points(&self) -> &[(u64, u64)] {
+        &self.points
+    }
+
+    /// Interpolates within the bracketing segment, clamping to the first and
+    /// last breakpoints outside their range.
+    pub fn eval(&self, x: u64) -> Option<u64> {
+        let first = *self.points.first()?;
+        let last = *self.points.last()?;
+        if x <= first.0 {
+            return Some(first.1);
+        }
+        if x >= last.0 {
+            return Some(last.1);
+        }
+        let upper = self.points.partition_point(|&(px, _)| px <= x);
+        let (x0, y0) = self.points[upper - 1];
+        let (x1, y1) = self.points[upper];
+        lerp(x0, y0, x1, y1, x)
+    }
+}
+
+/// Blends from `a` (at `t_bps == 0`) to `b` (at `t_bps == 10_000`) with
+/// quadratic ease-in progress `t^2`. Returns `None` when `t_bps > 10_000`.
+pub fn ease_in_quad(a: u64, b: u64, t_bps: u64) -> Option<u64> {
+    if t_bps > BPS_DENOMINATOR {
+        return None;
+    }
+    let t = t_bps as u128;
+    blend_bps(
+        a,
+        b,
+        checked_div_floor_wide(t * t, BPS_DENOMINATOR as u128)?,
+    )
+}
+
+/// Quadratic ease-out counterpart of `ease_in_quad`, with progress
+/// `1 - (1 - t)^2`.
+pub fn ease_out_quad(a: u64, b: u64, t_bps: u64) -> Option<u64> {
+    if t_bps > BPS_DENOMINATOR {
+        return None;
+    }
+    let t = t_bps as u128;
+    let progress = checked_div_floor_wide(
+        t * (2 * BPS_DENOMINATOR as u128 - t),
+        BPS_DENOMINATOR as u128,
+    )?;
+    blend_bps(a, b, progress)
+}
+
+fn blend_bps(a: u64, b: u64, progress_bps: u128) -> Option<u64> {
+    debug_assert!(progress_bps <= BPS_DENOMINATOR as u128);
+    let blended = a as u128 * (BPS_DENOMINATOR as u128 - progress_bps) + b as u128 * progress_bps;
+    checked_div_floor(blended, BPS_DENOMINATOR as u128)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curve_interpolates_and_clamps() {
+        let curve = Curve::new(vec![(0, 100), (10, 200), (20, 150)]).unwrap();
+        assert_eq!(curve.eval(0), Some(100));
+        assert_eq!(curve.eval(5), Some(150));
+        assert_eq!(curve.eval(10), Some(200));
+        assert_eq!(curve.eval(15), Some(175));
+        assert_eq!(curve.eval(u64::MAX), Some(150));
+        assert_eq!(Curve::new(vec![]), None);
+        assert_eq!(Curve::new(vec![(10, 0), (10, 1)]), None);
+    }
+
+    #[test]
+    fn try_weighted_average_rejects_weight_above_hundred() {
+        assert_eq!(
+            try_weighted_average(10u64, 20, 101),
+            Err(MathError::WeightOutOfRange)
+        );
+        assert_eq!(try_weighted_average(10u64, 20, 100), Ok(10));
+        assert_eq!(
+            try_weighted_average(u128::MAX, u128::MAX, 37),
+            Ok(u128::MAX)
+        );
+    }
+
+    #[test]
+    fn lerp_rejects_zero_span() {
+        assert_eq!(lerp(5, 10, 5, 20, 5), None);
+        assert_eq!(lerp(0, 10, 10, 20, 5), Some(15));
+        assert_eq!(lerp(10, 20, 0, 10, 5), Some(15));
+    }
+
+    #[test]
+    fn weighted_average_does_not_overflow_large_inputs() {
+        assert_eq!(weighted_average(u64::MAX, u64::MAX, 60), u64::MAX);
+        assert_eq!(weighted_average(u64::MAX, 0, 50), u64::MAX / 2);
+        assert_eq!(weighted_average(u64::MAX - 1, u64::MAX, 1), u64::MAX - 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn weighted_average_panics_when_weight_exceeds_hundred() {
+        weighted_average(10u64, 20, 101);
+    }
+
+    #[test]
+    fn weighted_average_n_requires_weights_summing_to_hundred() {
+        assert_eq!(weighted_average_n(&[]), None);
+        assert_eq!(weighted_average_n(&[(100, 50), (200, 49)]), None);
+        assert_eq!(weighted_average_n(&[(100, 50), (200, 51)]), None);
+        assert_eq!(
+            weighted_average_n(&[(100, 50), (200, 30), (300, 20)]),
+            Some(170)
+        );
+        assert_eq!(weighted_average_n(&[(u64::MAX, 100)]), Some(u64::MAX));
+        assert_eq!(
+            weighted_average_n(&[(900, 60), (400, 40)]),
+            Some(weighted_average(900, 400, 60))
+        );
+    }
+
+    #[test]
+    fn signed_blend_handles_mixed_signs() {
+        assert_eq!(weighted_average_signed(-100, 100, 50), Some(0));
+        assert_eq!(weighted_average_signed(-100, 100, 33), Some(34));
+        assert_eq!(weighted_average_signed(-100, 100, 101), None);
+        assert_eq!(weighted_average_signed(i128::MAX, 0, 60), None);
+    }
+
+    #[test]
+    fn signed_blend_rounds_toward_negative_infinity() {
+        assert_eq!(weighted_average_signed(-1, 0, 50), Some(-1));
+        assert_eq!(weighted_average_signed(-101, 0, 50), Some(-51));
+        assert_eq!(weighted_average_signed(101, 0, 50), Some(50));
+    }
+
+    #[test]
+    fn signed_net_goes_negative_on_net_outflow() {
+        assert_eq!(signed_net(5, 10), -5);
+        assert_eq!(signed_net(0, u64::MAX), -(u64::MAX as i128));
+        assert_eq!(signed_net(u64::MAX, 0), u64::MAX as i128);
+    }
+
+    #[test]
+    fn lerp_hits_endpoints_and_midpoint() {
+        assert_eq!(lerp(100, 1_000, 200, 2_000, 100), Some(1_000));
+        assert_eq!(lerp(100, 1_000, 200, 2_000, 200), Some(2_000));
+        assert_eq!(lerp(100, 1_000, 200, 2_000, 150), Some(1_500));
+        assert_eq!(lerp(100, 2_000, 200, 1_000, 150), Some(1_500));
+    }
+
+    #[test]
+    fn lerp_clamps_and_rounds_toward_the_left_point() {
+        assert_eq!(lerp(100, 1_000, 200, 2_000, 0), Some(1_000));
+        assert_eq!(lerp(100, 1_000, 200, 2_000, u64::MAX), Some(2_000));
+        assert_eq!(lerp(0, 0, 3, 1, 1), Some(0));
+        assert_eq!(lerp(0, 1, 3, 0, 1), Some(1));
+    }
+
+    #[test]
+    fn half_up_rounds_where_floor_truncates() {
+        assert_eq!(
+            weighted_average_rounded(3, 4, 50, RoundingMode::Floor),
+            Ok(3)
+        );
+        assert_eq!(
+            weighted_average_rounded(3, 4, 50, RoundingMode::HalfUp),
+            Ok(4)
+        );
+        assert_eq!(
+            weighted_average_rounded(3, 4, 50, RoundingMode::Floor),
+            Ok(weighted_average(3, 4, 50))
+        );
+        assert_eq!(
+            weighted_average_rounded(3, 4, 101, RoundingMode::HalfUp),
+            Err(MathError::WeightOutOfRange)
+        );
+    }
+
+    #[test]
+    fn quadratic_easing_pins_the_curve_shape() {
+        assert_eq!(ease_in_quad(0, 10_000, 0), Some(0));
+        assert_eq!(ease_in_quad(0, 10_000, 5_000), Some(2_500));
+        assert_eq!(ease_in_quad(0, 10_000, 10_000), Some(10_000));
+        assert_eq!(ease_out_quad(0, 10_000, 0), Some(0));
+        assert_eq!(ease_out_quad(0, 10_000, 5_000), Some(7_500));
+        assert_eq!(ease_out_quad(0, 10_000, 10_000), Some(10_000));
+        assert_eq!(ease_in_quad(0, 10_000, 10_001), None);
+        assert_eq!(ease_out_quad(0, 10_000, 10_001), None);
+    }
+
+    #[test]
+    fn bounded_blend_rejects_deviation_beyond_the_bound() {
+        assert_eq!(weighted_average_bounded(100, 110, 50, 1_000), Some(105));
+        assert_eq!(weighted_average_bounded(110, 100, 50, 1_000), Some(105));
+        assert_eq!(weighted_average_bounded(100, 111, 50, 1_000), None);
+        assert_eq!(weighted_average_bounded(0, 1, 50, 10_000), None);
+        assert_eq!(weighted_average_bounded(0, 0, 50, 0), Some(0));
+        assert_eq!(weighted_average_bounded(100, 100, 101, 0), None);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "weight_a <= PERCENT_DENOMINATOR")]
+    fn out_of_range_weight_trips_the_debug_assertion() {
+        weighted_average(10u64, 20, 101);
+    }
+
+    #[test]
+    fn debug_postconditions_hold_across_a_grid() {
+        let values = [0, 1, 99, 100, 10_001, u64::MAX / 2, u64::MAX];
+        for a in values {
+            for b in values {
+                for weight in [0, 1, 50, 99, 100] {
+                    let blended = weighted_average(a, b, weight);
+                    assert!(a.min(b) <= blended && blended <= a.max(b));
+                }
+                for t_bps in [0, 1, 5_000, 10_000] {
+                    assert!(ease_in_quad(a, b, t_bps).is_some());
+                    assert!(ease_out_quad(a, b, t_bps).is_some());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn bps_weight_expresses_fractional_percentages() {
+        assert_eq!(weighted_average_bps(1_000, 0, 6_250), Some(625));
+        assert_eq!(weighted_average_bps(0, 1_000, 6_250), Some(375));
+        assert_eq!(weighted_average_bps(8, 0, 6_250), Some(5));
+        assert_eq!(
+            weighted_average_bps(u64::MAX, u64::MAX, 6_250),
+            Some(u64::MAX)
+        );
+        assert_eq!(weighted_average_bps(1_000, 0, 10_001), None);
+    }
+
+    #[test]
+    fn bps_weight_matches_percent_weight_at_coarse_points() {
+        for (a, b) in [(0, 0), (1_000, 0), (12_345, 67_890), (u64::MAX, 1)] {
+            for weight in (10..=90).step_by(10) {
+                assert_eq!(
+                    weighted_average_bps(a, b, weight * 100),
+                    Some(weighted_average(a, b, weight))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn checked_weighted_average_matches_try_weighted_average() {
+        assert_eq!(checked_weighted_average(1_000, 2_000, 60), Ok(1_400));
+        assert_eq!(
+            checked_weighted_average(1_000, 2_000, 101),
+            Err(MathError::WeightOutOfRange)
+        );
+        for (a, b, weight) in [(0, 0, 0), (7, 3, 33), (u64::MAX, u64::MAX - 1, 99)] {
+            assert_eq!(
+                checked_weighted_average(a, b, weight),
+                try_weighted_average(a, b, weight)
+            );
+        }
+    }
+
+    /// Outputs captured before the 100 literal became `PERCENT_DENOMINATOR`;
+    /// any drift is a regression.
+    #[test]
+    fn named_denominators_keep_outputs_identical() {
+        const AMOUNTS: [u64; 6] = [0, 1, 99, 10_000, 123_456_789, u64::MAX / 2];
+        let blends: [(u64, [u64; 6]); 4] = [
+            (0, [7, 7, 7, 7, 7, 7]),
+            (37, [4, 4, 41, 3_704, 45_679_016, 3_412_647_653_636_267_053]),
+            (60, [2, 3, 62, 6_002, 74_074_076, 5_534_023_222_112_865_487]),
+            (
+                100,
+                [0, 1, 99, 10_000, 123_456_789, 9_223_372_036_854_775_807],
+            ),
+        ];
+        for (weight, expected) in blends {
+            let got = AMOUNTS.map(|amount| weighted_average(amount, 7, weight));
+            assert_eq!(got, expected, "weight = {weight}");
+        }
+    }
 }
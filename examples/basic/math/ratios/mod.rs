@@ -0,0 +1,202 @@
+pub mod basis_points;
+pub mod interpolation;
+pub mod price_impact;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MathError {
+    BpsOutOfRange,
+    WeightOutOfRange,
+    OutOfRange,
+    Overflow,
+    DivideByZero,
+}
+
+impl core::fmt::Display for MathError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let message = match self {
+            MathError::BpsOutOfRange => "basis points above 10_000",
+            MathError::WeightOutOfRange => "weight above 100",
+            MathError::OutOfRange => "value out of range",
+            MathError::Overflow => "arithmetic overflow",
+            MathError::DivideByZero => "division by zero",
+        };
+        f.write_str(message)
+    }
+}
+
+/// Quantities the fee and blend helpers are generic over (`u64`, `u128`,
+/// `Amount`). Values only add to and subtract from one another; scaling and
+/// division take plain `u64` factors, so two amounts are never multiplied.
+pub trait FeeInt: Copy + PartialOrd {
+    const ZERO: Self;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn checked_scale(self, factor: u64) -> Option<Self>;
+    /// Floor quotient and remainder; `None` when `divisor == 0`.
+    fn checked_div_rem(self, divisor: u64) -> Option<(Self, Self)>;
+}
+
+macro_rules! impl_fee_int {
+    ($($ty:ty),*) => {$(
+        impl FeeInt for $ty {
+            const ZERO: Self = 0;
+
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                <$ty>::checked_add(self, rhs)
+            }
+
+            fn checked_sub(self, rhs: Self) -> Option<Self> {
+                <$ty>::checked_sub(self, rhs)
+            }
+
+            fn checked_scale(self, factor: u64) -> Option<Self> {
+                <$ty>::checked_mul(self, factor as $ty)
+            }
+
+            fn checked_div_rem(self, divisor: u64) -> Option<(Self, Self)> {
+                let quotient = checked_div_floor_wide(self as u128, divisor as u128)?;
+                let remainder = self as u128 - quotient * divisor as u128;
+                Some((quotient as $ty, remainder as $ty))
+            }
+        }
+    )*};
+}
+
+impl_fee_int!(u64, u128);
+
+/// `floor(value * numerator / denominator)` computed as quotient and remainder
+/// parts so no intermediate wider than `T` is needed when
+/// `numerator <= denominator`.
+pub(crate) fn mul_div_floor<T: FeeInt>(value: T, numerator: u64, denominator: u64) -> Option<T> {
+    let (quotient, remainder) = value.checked_div_rem(denominator)?;
+    let whole = quotient.checked_scale(numerator)?;
+    let (part, _) = remainder
+        .checked_scale(numerator)?
+        .checked_div_rem(denominator)?;
+    whole.checked_add(part)
+}
+
+/// `floor(numerator / denominator)` narrowed to `u64`. Returns `None` when
+/// `denominator == 0` or the quotient does not fit in a `u64`.
+pub const fn checked_div_floor(numerator: u128, denominator: u128) -> Option<u64> {
+    match checked_div_floor_wide(numerator, denominator) {
+        Some(quotient) if quotient <= u64::MAX as u128 => Some(quotient as u64),
+        _ => None,
+    }
+}
+
+/// `checked_div_floor` without the narrowing, for quotients that stay `u128`.
+/// Every unsigned division in the library code goes through this function.
+pub(crate) const fn checked_div_floor_wide(numerator: u128, denominator: u128) -> Option<u128> {
+    if denominator == 0 {
+        return None;
+    }
+    Some(numerator / denominator)
+}
+
+#[cfg(all(feature = "checked-math", feature = "saturating-math"))]
+compile_error!("features `checked-math` and `saturating-math` are mutually exclusive");
+
+/// Single place the overflow policy is decided, applied where a result can
+/// genuinely exceed `u64::MAX`: summed route quotes, the rebalance gross and
+/// `accrue_interest`. With `checked-math` an overflowed (`None`) result panics;
+/// otherwise it is replaced by `saturated`, which saturates at the type's
+/// maximum (`Amount::MAX` for route totals and the gross). Saturating is the
+/// default, so `saturating-math` is never read here: it only states the policy
+/// explicitly and is rejected alongside `checked-math`.
+#[track_caller]
+pub(crate) fn resolve_overflow<T>(checked: Option<T>, saturated: T) -> T {
+    match checked {
+        Some(value) => value,
+        #[cfg(feature = "checked-math")]
+        None => {
+            let _ = saturated;
+            panic!("arithmetic overflow");
+        }
+        #[cfg(not(feature = "checked-math"))]
+        None => saturated,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RoundingMode {
+    Floor,
+    Ceil,
+    NearestEven,
+    HalfUp,
+}
+
+impl RoundingMode {
+    /// Panics when `denominator == 0`.
+    pub(crate) const fn divide(self, numerator: u128, denominator: u128) -> u128 {
+        let quotient = checked_div_floor_wide(numerator, denominator)
+            .expect("RoundingMode::divide needs a nonzero denominator");
+        let remainder = numerator - quotient * denominator;
+        if remainder == 0 {
+            return quotient;
+        }
+        match self {
+            RoundingMode::Floor => quotient,
+            RoundingMode::Ceil => quotient + 1,
+            RoundingMode::HalfUp => quotient + (remainder * 2 >= denominator) as u128,
+            RoundingMode::NearestEven => {
+                let doubled = remainder * 2;
+                if doubled < denominator {
+                    quotient
+                } else if doubled > denominator {
+                    quotient + 1
+                } else {
+                    quotient + (quotient & 1)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::basis_points::{apply_fee_with_denominator, try_apply_fee_with_denominator};
+    use super::price_impact::price_impact_bps;
+    use super::*;
+    use crate::math::decimal::Decimal;
+
+    #[test]
+    fn zero_denominator_is_none() {
+        assert_eq!(checked_div_floor(7, 0), None);
+        assert_eq!(checked_div_floor_wide(7, 0), None);
+        assert_eq!(7u64.checked_div_rem(0), None);
+        assert_eq!(mul_div_floor(7u64, 1, 0), None);
+    }
+
+    #[test]
+    fn narrowing_overflow_is_none() {
+        assert_eq!(checked_div_floor(u64::MAX as u128 + 1, 1), None);
+        assert_eq!(checked_div_floor(u64::MAX as u128 * 2, 2), Some(u64::MAX));
+        assert_eq!(checked_div_floor(7, 2), Some(3));
+    }
+
+    #[test]
+    fn zero_denominators_surface_through_callers() {
+        assert_eq!(
+            try_apply_fee_with_denominator(1_000, 0, 0),
+            Err(MathError::DivideByZero)
+        );
+        assert_eq!(apply_fee_with_denominator(1_000, 0, 0), None);
+        assert_eq!(price_impact_bps(0, 1_000, 0), None);
+        assert_eq!(Decimal::from_ratio(1, 0), None);
+        assert_eq!(Decimal::ONE.checked_div(Decimal::default()), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn rounding_mode_round_trips_through_json() {
+        let json = serde_json::to_string(&RoundingMode::NearestEven).unwrap();
+        assert_eq!(json, r#""NearestEven""#);
+        assert_eq!(
+            serde_json::from_str::<RoundingMode>(&json).unwrap(),
+            RoundingMode::NearestEven
+        );
+    }
+}
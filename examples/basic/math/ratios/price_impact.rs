@@ -0,0 +1,38 @@
+use super::basis_points::BPS_DENOMINATOR;
+use super::checked_div_floor;
+
+/// Basis-point gap between the spot price `reserve_out / reserve_in` and the
+/// effective price of swapping `amount_in` through a fee-less constant-product
+/// pool. Their ratio is `reserve_in / (reserve_in + amount_in)`, so the impact
+/// is `amount_in * 10_000 / (reserve_in + amount_in)`, rounded down. Returns
+/// `None` when either reserve is zero.
+pub const fn price_impact_bps(reserve_in: u64, reserve_out: u64, amount_in: u64) -> Option<u64> {
+    if reserve_in == 0 || reserve_out == 0 {
+        return None;
+    }
+    let impact = checked_div_floor(
+        amount_in as u128 * BPS_DENOMINATOR as u128,
+        reserve_in as u128 + amount_in as u128,
+    );
+    debug_assert!(matches!(impact, Some(bps) if bps <= BPS_DENOMINATOR));
+    impact
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn price_impact_matches_hand_computed_pools() {
+        assert_eq!(price_impact_bps(1_000_000, 2_000_000, 10_000), Some(99));
+        assert_eq!(price_impact_bps(1_000, 1_000, 1_000), Some(5_000));
+        assert_eq!(price_impact_bps(1_000, 1_000, 0), Some(0));
+        assert_eq!(price_impact_bps(1, 1, u64::MAX), Some(9_999));
+    }
+
+    #[test]
+    fn price_impact_rejects_empty_reserves() {
+        assert_eq!(price_impact_bps(0, 1_000, 10), None);
+        assert_eq!(price_impact_bps(1_000, 0, 10), None);
+    }
+}